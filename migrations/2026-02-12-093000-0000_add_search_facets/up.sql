@@ -0,0 +1,17 @@
+DROP INDEX search_etablissement_idx;
+
+CREATE INDEX search_etablissement_idx ON etablissement
+USING bm25 (siret, siren, date_debut, (code_postal::pdb.literal), (etat_administratif::pdb.literal), (activite_principale::pdb.literal), (tranche_effectifs::pdb.literal), (libelle_commune::pdb.ngram(4,5)), (search_denomination::pdb.ngram(4,5)))
+WITH (key_field='siret');
+
+DROP TABLE "public"."etablissement_staging";
+CREATE TABLE "public"."etablissement_staging" (LIKE "public"."etablissement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+DROP INDEX search_unite_legale_idx;
+
+CREATE INDEX search_unite_legale_idx ON unite_legale
+USING bm25 (siren, date_creation, date_debut, (etat_administratif::pdb.literal), (activite_principale::pdb.literal), (categorie_juridique::pdb.literal), (categorie_entreprise::pdb.literal), (tranche_effectifs::pdb.literal), (search_denomination::pdb.ngram(4,5)))
+WITH (key_field='siren');
+
+DROP TABLE "public"."unite_legale_staging";
+CREATE TABLE "public"."unite_legale_staging" (LIKE "public"."unite_legale" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,11 @@
+-- Names of natural persons are only suggested when their diffusion is allowed
+ALTER TABLE unite_legale ADD COLUMN suggest_denomination TEXT GENERATED ALWAYS AS (coalesce(denomination, '') || ' ' || coalesce(sigle, '') || ' ' || coalesce(denomination_usuelle_1, '') || ' ' || (CASE WHEN statut_diffusion = 'O' THEN coalesce(prenom_usuel, '') || ' ' || coalesce(nom_usage, '') || ' ' || coalesce(nom, '') ELSE '' END)) STORED;
+
+DROP INDEX search_unite_legale_idx;
+
+CREATE INDEX search_unite_legale_idx ON unite_legale
+USING bm25 (siren, date_creation, date_debut, (etat_administratif::pdb.literal), (activite_principale::pdb.literal), (categorie_juridique::pdb.literal), (categorie_entreprise::pdb.literal), (tranche_effectifs::pdb.literal), (search_denomination::pdb.ngram(4,5)), (suggest_denomination::pdb.ngram(2,15,'prefix_only=true')))
+WITH (key_field='siren');
+
+DROP TABLE "public"."unite_legale_staging";
+CREATE TABLE "public"."unite_legale_staging" (LIKE "public"."unite_legale" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
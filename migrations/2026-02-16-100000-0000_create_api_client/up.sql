@@ -0,0 +1,18 @@
+CREATE TABLE "public"."api_client"
+(
+    "id" serial,
+    "name" text NOT NULL,
+    "key_prefix" text NOT NULL,
+    "key_hash" text NOT NULL,
+    "scopes" text[] NOT NULL,
+    "expires_at" timestamptz,
+    "revoked_at" timestamptz,
+    "created_at" timestamptz NOT NULL DEFAULT NOW(),
+    "updated_at" timestamptz NOT NULL DEFAULT NOW(),
+    PRIMARY KEY ("id")
+);
+
+CREATE UNIQUE INDEX "api_client_key_hash_index" ON "public"."api_client" USING BTREE
+("key_hash");
+
+SELECT diesel_manage_updated_at('api_client');
@@ -0,0 +1,4 @@
+ALTER TABLE unite_legale DROP COLUMN numero_tva_intracommunautaire;
+
+DROP TABLE "public"."unite_legale_staging";
+CREATE TABLE "public"."unite_legale_staging" (LIKE "public"."unite_legale" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,5 @@
+-- FR, then the key (12 + 3 * (SIREN modulo 97)) modulo 97, then the SIREN
+ALTER TABLE unite_legale ADD COLUMN numero_tva_intracommunautaire TEXT GENERATED ALWAYS AS (CASE WHEN siren ~ '^[0-9]{9}$' THEN 'FR' || lpad(((12 + 3 * (siren::bigint % 97)) % 97)::text, 2, '0') || siren END) STORED;
+
+DROP TABLE "public"."unite_legale_staging";
+CREATE TABLE "public"."unite_legale_staging" (LIKE "public"."unite_legale" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
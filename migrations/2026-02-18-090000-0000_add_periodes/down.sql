@@ -0,0 +1,7 @@
+DELETE FROM "public"."group_metadata"
+    WHERE "group_type" IN ('unites_legales_periodes', 'etablissements_periodes');
+
+DROP TABLE "public"."unite_legale_periode" CASCADE;
+DROP TABLE "public"."unite_legale_periode_staging" CASCADE;
+DROP TABLE "public"."etablissement_periode" CASCADE;
+DROP TABLE "public"."etablissement_periode_staging" CASCADE;
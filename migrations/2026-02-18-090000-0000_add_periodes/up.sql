@@ -0,0 +1,71 @@
+INSERT INTO "public"."group_metadata"
+    ("group_type", "insee_name", "file_name", "url")
+VALUES
+    ('unites_legales_periodes', 'Unités Légales Historique', 'StockUniteLegaleHistorique_utf8', 'https://object.files.data.gouv.fr/data-pipeline-open/siren/stock/StockUniteLegaleHistorique_utf8.zip'),
+    ('etablissements_periodes', 'Établissements Historique', 'StockEtablissementHistorique_utf8', 'https://object.files.data.gouv.fr/data-pipeline-open/siren/stock/StockEtablissementHistorique_utf8.zip');
+
+CREATE TABLE "public"."unite_legale_periode"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "siren" varchar(9) NOT NULL,
+    "date_fin" date,
+    "date_debut" date,
+    "etat_administratif" varchar(1) NOT NULL,
+    "changement_etat_administratif" bool NOT NULL,
+    "nom" text,
+    "changement_nom" bool NOT NULL,
+    "nom_usage" text,
+    "changement_nom_usage" bool NOT NULL,
+    "denomination" text,
+    "changement_denomination" bool NOT NULL,
+    "denomination_usuelle_1" text,
+    "denomination_usuelle_2" text,
+    "denomination_usuelle_3" text,
+    "changement_denomination_usuelle" bool NOT NULL,
+    "categorie_juridique" text,
+    "changement_categorie_juridique" bool NOT NULL,
+    "activite_principale" text,
+    "nomenclature_activite_principale" text,
+    "changement_activite_principale" bool NOT NULL,
+    "nic_siege" text,
+    "changement_nic_siege" bool NOT NULL,
+    "economie_sociale_solidaire" text,
+    "changement_economie_sociale_solidaire" bool NOT NULL,
+    "societe_mission" varchar(1),
+    "changement_societe_mission" bool NOT NULL,
+    "caractere_employeur" varchar(1),
+    "changement_caractere_employeur" bool NOT NULL
+);
+
+CREATE INDEX "unite_legale_periode_siren_index" ON "public"."unite_legale_periode" USING BTREE
+("siren", "date_debut");
+
+CREATE TABLE "public"."unite_legale_periode_staging" (LIKE "public"."unite_legale_periode" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE TABLE "public"."etablissement_periode"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "siren" varchar(9) NOT NULL,
+    "nic" text NOT NULL,
+    "siret" varchar(14) NOT NULL,
+    "date_fin" date,
+    "date_debut" date,
+    "etat_administratif" varchar(1) NOT NULL,
+    "changement_etat_administratif" bool NOT NULL,
+    "enseigne_1" text,
+    "enseigne_2" text,
+    "enseigne_3" text,
+    "changement_enseigne" bool NOT NULL,
+    "denomination_usuelle" text,
+    "changement_denomination_usuelle" bool NOT NULL,
+    "activite_principale" text,
+    "nomenclature_activite_principale" text,
+    "changement_activite_principale" bool NOT NULL,
+    "caractere_employeur" text,
+    "changement_caractere_employeur" bool NOT NULL
+);
+
+CREATE INDEX "etablissement_periode_siret_index" ON "public"."etablissement_periode" USING BTREE
+("siret", "date_debut");
+
+CREATE TABLE "public"."etablissement_periode_staging" (LIKE "public"."etablissement_periode" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS "public"."lien_succession_natural_key_index";
+
+DROP TABLE "public"."lien_succession_staging";
+CREATE TABLE "public"."lien_succession_staging" (LIKE "public"."lien_succession" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,20 @@
+-- Keep the most recently processed copy of each link
+DELETE FROM "public"."lien_succession"
+WHERE "id" IN (
+    SELECT "id" FROM (
+        SELECT
+            "id",
+            row_number() OVER (
+                PARTITION BY "siret_etablissement_predecesseur", "siret_etablissement_successeur", "date_lien_succession"
+                ORDER BY "date_dernier_traitement_lien_succession" DESC NULLS LAST, "id"
+            ) AS "rank"
+        FROM "public"."lien_succession"
+    ) AS "ranked"
+    WHERE "rank" > 1
+);
+
+CREATE UNIQUE INDEX "lien_succession_natural_key_index" ON "public"."lien_succession" USING BTREE
+("siret_etablissement_predecesseur", "siret_etablissement_successeur", "date_lien_succession");
+
+DROP TABLE "public"."lien_succession_staging";
+CREATE TABLE "public"."lien_succession_staging" (LIKE "public"."lien_succession" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,36 @@
+-- The INSEE publishes the correspondence table as a spreadsheet only, its
+-- CSV conversion has no default location
+INSERT INTO "public"."group_metadata"
+    ("group_type", "insee_name", "file_name", "url")
+VALUES
+    ('naf_correspondances', 'Correspondances NAF rév. 2 - NAF 2025', 'TablePassageNafRev2Naf2025_utf8', '');
+
+CREATE TABLE "public"."naf_correspondance"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "naf_rev2" varchar(6) NOT NULL,
+    "naf25" varchar(6) NOT NULL
+);
+
+CREATE UNIQUE INDEX naf_correspondance_naf_rev2_index ON "public"."naf_correspondance" ("naf_rev2", "naf25");
+CREATE INDEX naf_correspondance_naf25_index ON "public"."naf_correspondance" ("naf25");
+
+CREATE TABLE "public"."naf_correspondance_staging" (LIKE "public"."naf_correspondance" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+DROP INDEX search_etablissement_idx;
+
+CREATE INDEX search_etablissement_idx ON etablissement
+USING bm25 (siret, siren, date_debut, (code_postal::pdb.literal), (etat_administratif::pdb.literal), (activite_principale::pdb.literal), (nomenclature_activite_principale::pdb.literal), (activite_principale_naf25::pdb.literal), (tranche_effectifs::pdb.literal), (libelle_commune::pdb.ngram(4,5)), (search_denomination::pdb.ngram(4,5)))
+WITH (key_field='siret');
+
+DROP TABLE "public"."etablissement_staging";
+CREATE TABLE "public"."etablissement_staging" (LIKE "public"."etablissement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+DROP INDEX search_unite_legale_idx;
+
+CREATE INDEX search_unite_legale_idx ON unite_legale
+USING bm25 (siren, date_creation, date_debut, (etat_administratif::pdb.literal), (activite_principale::pdb.literal), (nomenclature_activite_principale::pdb.literal), (activite_principale_naf25::pdb.literal), (categorie_juridique::pdb.literal), (categorie_entreprise::pdb.literal), (tranche_effectifs::pdb.literal), (search_denomination::pdb.ngram(4,5)), (suggest_denomination::pdb.ngram(2,15,'prefix_only=true')))
+WITH (key_field='siren');
+
+DROP TABLE "public"."unite_legale_staging";
+CREATE TABLE "public"."unite_legale_staging" (LIKE "public"."unite_legale" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
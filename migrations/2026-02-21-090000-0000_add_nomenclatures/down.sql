@@ -0,0 +1,5 @@
+DELETE FROM "public"."group_metadata"
+    WHERE "group_type" = 'nomenclatures';
+
+DROP TABLE "public"."nomenclature" CASCADE;
+DROP TABLE "public"."nomenclature_staging" CASCADE;
@@ -0,0 +1,84 @@
+-- The INSEE publishes its nomenclatures as spreadsheets only, their CSV
+-- conversion has no default location
+INSERT INTO "public"."group_metadata"
+    ("group_type", "insee_name", "file_name", "url")
+VALUES
+    ('nomenclatures', 'Nomenclatures', 'Nomenclatures_utf8', '');
+
+CREATE TABLE "public"."nomenclature"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "nom" varchar(32) NOT NULL,
+    "code" varchar(8) NOT NULL,
+    "libelle" text NOT NULL,
+    "niveau" smallint,
+    "parent" varchar(8)
+);
+
+CREATE UNIQUE INDEX nomenclature_code_index ON "public"."nomenclature" ("nom", "code");
+
+CREATE TABLE "public"."nomenclature_staging" (LIKE "public"."nomenclature" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+-- Short and stable nomenclatures, available before the first import
+INSERT INTO "public"."nomenclature"
+    ("nom", "code", "libelle")
+VALUES
+    ('tranches_effectifs', 'NN', 'Unité non employeuse'),
+    ('tranches_effectifs', '00', '0 salarié'),
+    ('tranches_effectifs', '01', '1 ou 2 salariés'),
+    ('tranches_effectifs', '02', '3 à 5 salariés'),
+    ('tranches_effectifs', '03', '6 à 9 salariés'),
+    ('tranches_effectifs', '11', '10 à 19 salariés'),
+    ('tranches_effectifs', '12', '20 à 49 salariés'),
+    ('tranches_effectifs', '21', '50 à 99 salariés'),
+    ('tranches_effectifs', '22', '100 à 199 salariés'),
+    ('tranches_effectifs', '31', '200 à 249 salariés'),
+    ('tranches_effectifs', '32', '250 à 499 salariés'),
+    ('tranches_effectifs', '41', '500 à 999 salariés'),
+    ('tranches_effectifs', '42', '1 000 à 1 999 salariés'),
+    ('tranches_effectifs', '51', '2 000 à 4 999 salariés'),
+    ('tranches_effectifs', '52', '5 000 à 9 999 salariés'),
+    ('tranches_effectifs', '53', '10 000 salariés et plus'),
+    ('types_voie', 'ALL', 'Allée'),
+    ('types_voie', 'AV', 'Avenue'),
+    ('types_voie', 'BD', 'Boulevard'),
+    ('types_voie', 'CAR', 'Carrefour'),
+    ('types_voie', 'CHE', 'Chemin'),
+    ('types_voie', 'CHS', 'Chaussée'),
+    ('types_voie', 'CITE', 'Cité'),
+    ('types_voie', 'COR', 'Corniche'),
+    ('types_voie', 'CRS', 'Cours'),
+    ('types_voie', 'DOM', 'Domaine'),
+    ('types_voie', 'DSC', 'Descente'),
+    ('types_voie', 'ECA', 'Ecart'),
+    ('types_voie', 'ESP', 'Esplanade'),
+    ('types_voie', 'FG', 'Faubourg'),
+    ('types_voie', 'GR', 'Grande Rue'),
+    ('types_voie', 'HAM', 'Hameau'),
+    ('types_voie', 'HLE', 'Halle'),
+    ('types_voie', 'IMP', 'Impasse'),
+    ('types_voie', 'LD', 'Lieu-dit'),
+    ('types_voie', 'LOT', 'Lotissement'),
+    ('types_voie', 'MAR', 'Marché'),
+    ('types_voie', 'MTE', 'Montée'),
+    ('types_voie', 'PARC', 'Parc'),
+    ('types_voie', 'PAS', 'Passage'),
+    ('types_voie', 'PL', 'Place'),
+    ('types_voie', 'PLN', 'Plaine'),
+    ('types_voie', 'PLT', 'Plateau'),
+    ('types_voie', 'PRO', 'Promenade'),
+    ('types_voie', 'PRV', 'Parvis'),
+    ('types_voie', 'QUA', 'Quartier'),
+    ('types_voie', 'QUAI', 'Quai'),
+    ('types_voie', 'RES', 'Résidence'),
+    ('types_voie', 'RLE', 'Ruelle'),
+    ('types_voie', 'ROC', 'Rocade'),
+    ('types_voie', 'RPT', 'Rond-point'),
+    ('types_voie', 'RTE', 'Route'),
+    ('types_voie', 'RUE', 'Rue'),
+    ('types_voie', 'SEN', 'Sentier'),
+    ('types_voie', 'SQ', 'Square'),
+    ('types_voie', 'TPL', 'Terre-plein'),
+    ('types_voie', 'TRA', 'Traverse'),
+    ('types_voie', 'VLA', 'Villa'),
+    ('types_voie', 'VLGE', 'Village');
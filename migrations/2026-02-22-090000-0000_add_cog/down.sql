@@ -0,0 +1,20 @@
+DELETE FROM "public"."group_metadata"
+    WHERE "group_type" = 'cog';
+
+DROP INDEX "etablissement_code_commune_index";
+
+DROP TABLE "public"."etablissement_staging";
+CREATE TABLE "public"."etablissement_staging" (LIKE "public"."etablissement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+DROP TABLE "public"."cog_commune_courante" CASCADE;
+DROP TABLE "public"."cog_commune_courante_staging" CASCADE;
+DROP TABLE "public"."cog_mouvement_commune" CASCADE;
+DROP TABLE "public"."cog_mouvement_commune_staging" CASCADE;
+DROP TABLE "public"."cog_region" CASCADE;
+DROP TABLE "public"."cog_region_staging" CASCADE;
+DROP TABLE "public"."cog_departement" CASCADE;
+DROP TABLE "public"."cog_departement_staging" CASCADE;
+DROP TABLE "public"."cog_arrondissement" CASCADE;
+DROP TABLE "public"."cog_arrondissement_staging" CASCADE;
+DROP TABLE "public"."cog_commune" CASCADE;
+DROP TABLE "public"."cog_commune_staging" CASCADE;
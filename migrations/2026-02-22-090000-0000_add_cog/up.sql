@@ -0,0 +1,118 @@
+INSERT INTO "public"."group_metadata"
+    ("group_type", "insee_name", "file_name", "url")
+VALUES
+    ('cog', 'Code officiel géographique', 'cog_ensemble_2025_csv', 'https://www.insee.fr/fr/statistiques/fichier/8377162/cog_ensemble_2025_csv.zip');
+
+-- Columns follow the INSEE files, names being given by tncc (type of name),
+-- ncc (uppercase), nccenr (rich typography) and libelle (with article)
+CREATE TABLE "public"."cog_commune"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "type_commune" varchar(4) NOT NULL,
+    "code_commune" varchar(5) NOT NULL,
+    "code_region" varchar(2),
+    "code_departement" varchar(3),
+    "code_collectivite" varchar(4),
+    "code_arrondissement" varchar(4),
+    "tncc" varchar(1),
+    "ncc" text,
+    "nccenr" text,
+    "libelle" text,
+    "code_canton" varchar(5),
+    "code_commune_parent" varchar(5)
+);
+
+CREATE UNIQUE INDEX cog_commune_code_index ON "public"."cog_commune" ("code_commune", "type_commune");
+
+CREATE TABLE "public"."cog_commune_staging" (LIKE "public"."cog_commune" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE TABLE "public"."cog_arrondissement"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "code_arrondissement" varchar(4) NOT NULL,
+    "code_departement" varchar(3) NOT NULL,
+    "code_region" varchar(2) NOT NULL,
+    "cheflieu" varchar(5),
+    "tncc" varchar(1),
+    "ncc" text,
+    "nccenr" text,
+    "libelle" text
+);
+
+CREATE UNIQUE INDEX cog_arrondissement_code_index ON "public"."cog_arrondissement" ("code_arrondissement");
+
+CREATE TABLE "public"."cog_arrondissement_staging" (LIKE "public"."cog_arrondissement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE TABLE "public"."cog_departement"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "code_departement" varchar(3) NOT NULL,
+    "code_region" varchar(2) NOT NULL,
+    "cheflieu" varchar(5),
+    "tncc" varchar(1),
+    "ncc" text,
+    "nccenr" text,
+    "libelle" text
+);
+
+CREATE UNIQUE INDEX cog_departement_code_index ON "public"."cog_departement" ("code_departement");
+
+CREATE TABLE "public"."cog_departement_staging" (LIKE "public"."cog_departement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE TABLE "public"."cog_region"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "code_region" varchar(2) NOT NULL,
+    "cheflieu" varchar(5),
+    "tncc" varchar(1),
+    "ncc" text,
+    "nccenr" text,
+    "libelle" text
+);
+
+CREATE UNIQUE INDEX cog_region_code_index ON "public"."cog_region" ("code_region");
+
+CREATE TABLE "public"."cog_region_staging" (LIKE "public"."cog_region" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE TABLE "public"."cog_mouvement_commune"
+(
+    "id" UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    "type_evenement" varchar(2) NOT NULL,
+    "date_effet" date NOT NULL,
+    "type_commune_avant" varchar(4) NOT NULL,
+    "code_commune_avant" varchar(5) NOT NULL,
+    "tncc_avant" varchar(1),
+    "ncc_avant" text,
+    "nccenr_avant" text,
+    "libelle_avant" text,
+    "type_commune_apres" varchar(4) NOT NULL,
+    "code_commune_apres" varchar(5) NOT NULL,
+    "tncc_apres" varchar(1),
+    "ncc_apres" text,
+    "nccenr_apres" text,
+    "libelle_apres" text
+);
+
+CREATE INDEX cog_mouvement_commune_avant_index ON "public"."cog_mouvement_commune" ("code_commune_avant");
+
+CREATE TABLE "public"."cog_mouvement_commune_staging" (LIKE "public"."cog_mouvement_commune" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+-- Every commune code ever used, current or not, with the current commune
+-- it belongs to, built from the tables above on each import
+CREATE TABLE "public"."cog_commune_courante"
+(
+    "code_commune" varchar(5) PRIMARY KEY,
+    "code_commune_courante" varchar(5) NOT NULL,
+    "code_departement" varchar(3) NOT NULL,
+    "code_region" varchar(2) NOT NULL
+);
+
+CREATE INDEX cog_commune_courante_departement_index ON "public"."cog_commune_courante" ("code_departement");
+CREATE INDEX cog_commune_courante_region_index ON "public"."cog_commune_courante" ("code_region");
+
+CREATE TABLE "public"."cog_commune_courante_staging" (LIKE "public"."cog_commune_courante" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
+
+CREATE INDEX "etablissement_code_commune_index" ON "public"."etablissement" USING BTREE ("code_commune");
+
+DROP TABLE "public"."etablissement_staging";
+CREATE TABLE "public"."etablissement_staging" (LIKE "public"."etablissement" INCLUDING DEFAULTS INCLUDING CONSTRAINTS INCLUDING IDENTITY INCLUDING INDEXES INCLUDING GENERATED);
@@ -0,0 +1,94 @@
+use super::common::CmdApiScope;
+use crate::connectors::ConnectorsBuilders;
+use crate::models::api_client::{self, error::Error};
+use chrono::{Duration, Utc};
+use serde::Serialize;
+use std::process;
+use tracing::error;
+
+#[derive(clap::Parser, Debug)]
+pub struct ApiClientFlags {
+    #[clap(subcommand)]
+    subcmd: ApiClientSubCommand,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum ApiClientSubCommand {
+    /// Create a client and print its key, which cannot be retrieved afterwards
+    #[clap(name = "create")]
+    Create {
+        /// Name identifying the client
+        #[clap(long = "name")]
+        name: String,
+
+        /// Scopes granted to the client
+        #[clap(value_enum, long = "scope", required = true)]
+        scopes: Vec<CmdApiScope>,
+
+        /// Number of days before the key expires, never expires by default
+        #[clap(long = "expires-in-days")]
+        expires_in_days: Option<u32>,
+    },
+
+    /// List all clients, revoked and expired ones included
+    #[clap(name = "list")]
+    List,
+
+    /// Revoke the key of a client
+    #[clap(name = "revoke")]
+    Revoke {
+        /// Identifier of the client
+        id: i32,
+    },
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("Unable to stringify API client")
+    );
+}
+
+fn exit(error: Error) -> ! {
+    error!("{}", error);
+    process::exit(1);
+}
+
+pub async fn run(flags: ApiClientFlags, builders: ConnectorsBuilders) {
+    let connectors = builders.create();
+
+    match flags.subcmd {
+        ApiClientSubCommand::Create {
+            name,
+            scopes,
+            expires_in_days,
+        } => {
+            let scopes = scopes.into_iter().fold(Vec::new(), |mut scopes, scope| {
+                let scope = scope.into();
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+                scopes
+            });
+
+            let expires_at =
+                expires_in_days.map(|days| Utc::now() + Duration::days(i64::from(days)));
+
+            match api_client::create(&connectors, name, scopes, expires_at) {
+                Ok(created) => {
+                    print_json(&created.client);
+                    println!("API key (shown only once): {}", created.key);
+                }
+                Err(error) => exit(error),
+            }
+        }
+        ApiClientSubCommand::List => match api_client::list(&connectors) {
+            Ok(clients) => print_json(&clients),
+            Err(error) => exit(error),
+        },
+        ApiClientSubCommand::Revoke { id } => match api_client::revoke(&connectors, id) {
+            Ok(client) => print_json(&client),
+            Err(error) => exit(error),
+        },
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::api_client::common::ApiScope;
 use crate::models::update_metadata::common::SyntheticGroupType;
 use serde::Deserialize;
 
@@ -6,6 +7,11 @@ pub enum CmdGroupType {
     UnitesLegales,
     Etablissements,
     LiensSuccession,
+    UnitesLegalesPeriodes,
+    EtablissementsPeriodes,
+    NafCorrespondances,
+    Nomenclatures,
+    Cog,
     All,
 }
 
@@ -15,7 +21,31 @@ impl From<CmdGroupType> for SyntheticGroupType {
             CmdGroupType::UnitesLegales => SyntheticGroupType::UnitesLegales,
             CmdGroupType::Etablissements => SyntheticGroupType::Etablissements,
             CmdGroupType::LiensSuccession => SyntheticGroupType::LiensSuccession,
+            CmdGroupType::UnitesLegalesPeriodes => SyntheticGroupType::UnitesLegalesPeriodes,
+            CmdGroupType::EtablissementsPeriodes => SyntheticGroupType::EtablissementsPeriodes,
+            CmdGroupType::NafCorrespondances => SyntheticGroupType::NafCorrespondances,
+            CmdGroupType::Nomenclatures => SyntheticGroupType::Nomenclatures,
+            CmdGroupType::Cog => SyntheticGroupType::Cog,
             CmdGroupType::All => SyntheticGroupType::All,
         }
     }
 }
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy)]
+pub enum CmdApiScope {
+    Public,
+    Export,
+    Admin,
+    FullDiffusion,
+}
+
+impl From<CmdApiScope> for ApiScope {
+    fn from(scope: CmdApiScope) -> Self {
+        match scope {
+            CmdApiScope::Public => ApiScope::Public,
+            CmdApiScope::Export => ApiScope::Export,
+            CmdApiScope::Admin => ApiScope::Admin,
+            CmdApiScope::FullDiffusion => ApiScope::FullDiffusion,
+        }
+    }
+}
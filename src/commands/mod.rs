@@ -1,8 +1,10 @@
+mod api_client;
 mod common;
 mod serve;
 mod update;
 
 use crate::connectors::ConnectorsBuilders;
+use api_client::ApiClientFlags;
 use clap::Parser;
 use serve::ServeFlags;
 use update::UpdateFlags;
@@ -25,6 +27,10 @@ enum MainCommand {
     /// Serve data from database to /unites_legales/<siren> and /etablissements/<siret>
     #[clap(name = "serve")]
     Serve(ServeFlags),
+
+    /// Manage the API clients allowed to call the HTTP API
+    #[clap(name = "api-client")]
+    ApiClient(ApiClientFlags),
 }
 
 pub async fn run(builders: ConnectorsBuilders) {
@@ -33,5 +39,8 @@ pub async fn run(builders: ConnectorsBuilders) {
     match opts.main_command {
         MainCommand::Update(update_flags) => update::run(update_flags, builders).await,
         MainCommand::Serve(serve_flags) => serve::run(serve_flags, builders).await,
+        MainCommand::ApiClient(api_client_flags) => {
+            api_client::run(api_client_flags, builders).await
+        }
     }
 }
@@ -1,9 +1,12 @@
 mod runner;
 
 use crate::connectors::ConnectorsBuilders;
+use crate::models::api_client;
 use runner::common::Context;
+use runner::{RateLimitConfig, RateLimiter, StatusTokenSigner};
 use std::net::ToSocketAddrs;
-use tracing::info;
+use std::sync::Arc;
+use tracing::{info, warn};
 
 #[derive(clap::Args, Debug)]
 pub struct ServeFlags {
@@ -19,10 +22,38 @@ pub struct ServeFlags {
     #[clap(long = "host", env)]
     host: String,
 
-    /// API key needed to allow maintenance operation from HTTP
-    #[clap(long = "api-key", env)]
+    /// Deprecated, registered as an admin API client on start when no client exists
+    #[clap(long = "api-key", env, hide_env_values = true)]
     api_key: Option<String>,
 
+    /// Reject requests to the public API made without an API key
+    #[clap(long = "require-api-key", env)]
+    require_api_key: bool,
+
+    /// Requests per minute allowed for each API key, 0 disables the limit
+    #[clap(long = "rate-limit-per-key", env, default_value_t = 600)]
+    rate_limit_per_key: u32,
+
+    /// Requests per minute allowed for each anonymous IP, 0 disables the limit
+    #[clap(long = "rate-limit-per-ip", env, default_value_t = 120)]
+    rate_limit_per_ip: u32,
+
+    /// Searches, exports, batches and tiles allowed per day for each API key, 0 disables the quota
+    #[clap(long = "daily-quota-per-key", env, default_value_t = 0)]
+    daily_quota_per_key: u32,
+
+    /// Searches, exports, batches and tiles allowed per day for each anonymous IP, 0 disables the quota
+    #[clap(long = "daily-quota-per-ip", env, default_value_t = 1000)]
+    daily_quota_per_ip: u32,
+
+    /// Identify anonymous callers by the last X-Forwarded-For address, only behind a reverse proxy
+    #[clap(long = "trust-forwarded-for", env)]
+    trust_forwarded_for: bool,
+
+    /// Secret signing the update status tokens, random on each start by default
+    #[clap(long = "status-token-secret", env, hide_env_values = true)]
+    status_token_secret: Option<String>,
+
     /// Base URL needed to configure asynchronous polling for updates
     #[clap(long = "base-url", env)]
     base_url: Option<String>,
@@ -44,12 +75,30 @@ pub async fn run(flags: ServeFlags, builders: ConnectorsBuilders) {
 
     info!("Configuring for {:#?}", flags.environment);
 
+    if let Some(api_key) = flags.api_key.as_deref() {
+        warn!("--api-key is deprecated, create API clients with `sirene api-client create`");
+
+        if let Some(client) = api_client::bootstrap(&builders.create(), api_key)
+            .expect("Unable to register the API key as an API client")
+        {
+            info!("API key registered as the API client {}", client.id);
+        }
+    }
+
     runner::run(
         addr,
         Context {
             builders,
-            api_key: flags.api_key,
+            require_api_key: flags.require_api_key,
             base_url: flags.base_url,
+            rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig {
+                key_per_minute: flags.rate_limit_per_key,
+                ip_per_minute: flags.rate_limit_per_ip,
+                key_daily_quota: flags.daily_quota_per_key,
+                ip_daily_quota: flags.daily_quota_per_ip,
+                trust_forwarded_for: flags.trust_forwarded_for,
+            })),
+            status_token_signer: StatusTokenSigner::new(flags.status_token_secret),
         },
     )
     .await;
@@ -1,3 +1,4 @@
+use super::auth::{AdminClient, authenticate_admin};
 use super::common::{Context, StatusQueryString, UpdateOptions};
 use super::error::Error;
 use crate::models;
@@ -7,12 +8,13 @@ use axum::{
     Json,
     body::Body,
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
 use chrono::Utc;
 use serde::Serialize;
 use std::sync::Arc;
+use tracing::info;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
@@ -34,22 +36,17 @@ impl From<UpdateMetadata> for StatusCode {
     responses(
         (status = 202, description = "Update launched"),
         (status = 401, description = "Missing or invalid API key"),
+        (status = 403, description = "API key not granted the admin scope"),
         (status = 500, description = "Internal server error")
     ),
     tag = super::common::ADMIN_TAG
 )]
 async fn post_update(
     State(context): State<Arc<Context>>,
+    AdminClient(admin): AdminClient,
     Json(options): Json<UpdateOptions>,
 ) -> Result<Response, Error> {
-    let api_key = match &context.api_key {
-        Some(key) => key,
-        None => return Err(Error::MissingApiKey),
-    };
-
-    if &options.api_key != api_key {
-        return Err(Error::ApiKey);
-    }
+    info!("[Admin] Update requested by API client {}", admin.name);
 
     if options.asynchronous && context.base_url.is_none() {
         return Err(Error::MissingBaseUrlForAsync);
@@ -67,59 +64,68 @@ async fn post_update(
     )
     .await?;
 
-    reply_with_update_metadata(update_metadata, context.base_url.clone(), api_key)
+    reply_with_update_metadata(update_metadata, &context, None)
 }
 
 /// Get update status
 #[utoipa::path(
     get,
     path = "/update/status",
-    params(
-        ("api_key" = String, Query, description = "API key")
-    ),
+    params(StatusQueryString),
     responses(
         (status = 200, description = "Update finished"),
         (status = 202, description = "Update in progress"),
-        (status = 401, description = "Missing or invalid API key"),
+        (status = 401, description = "Missing or invalid API key or status token"),
+        (status = 403, description = "API key not granted the admin scope"),
         (status = 500, description = "Internal server error")
     ),
     tag = super::common::ADMIN_TAG
 )]
 async fn get_update_status(
     State(context): State<Arc<Context>>,
+    headers: HeaderMap,
     Query(query): Query<StatusQueryString>,
 ) -> Result<Response, Error> {
-    let api_key = match &context.api_key {
-        Some(key) => key,
-        None => return Err(Error::MissingApiKey),
-    };
-
-    if &query.api_key != api_key {
-        return Err(Error::ApiKey);
+    match query.token.as_deref() {
+        Some(token) if !context.status_token_signer.verify(token) => {
+            return Err(Error::InvalidStatusToken);
+        }
+        Some(_) => {}
+        None => {
+            authenticate_admin(&context, &headers)?;
+        }
     }
 
     let connectors = context.builders.create();
 
     let update_metadata = models::update_metadata::current_update(&connectors)?;
 
-    reply_with_update_metadata(update_metadata, context.base_url.clone(), api_key)
+    reply_with_update_metadata(update_metadata, &context, query.token.as_deref())
 }
 
+/// Polling with a status token gives back the same token, only clients
+/// authenticated by their key get a new one.
 fn reply_with_update_metadata<T: Serialize + Clone + Into<StatusCode>>(
     update_metadata: T,
-    base_url: Option<String>,
-    api_key: &str,
+    context: &Context,
+    token: Option<&str>,
 ) -> Result<Response, Error> {
     let status_code = update_metadata.clone().into();
     let mut response = Json(update_metadata).into_response();
     *response.status_mut() = status_code;
 
-    if let Some(base_url) = base_url {
+    if let Some(base_url) = &context.base_url {
         response.headers_mut().insert(
             "Location",
-            format!("{}/admin/update/status?api_key={}", base_url, api_key)
-                .parse()
-                .unwrap(),
+            format!(
+                "{}/admin/update/status?token={}",
+                base_url,
+                token
+                    .map(str::to_string)
+                    .unwrap_or_else(|| context.status_token_signer.sign())
+            )
+            .parse()
+            .unwrap(),
         );
         response
             .headers_mut()
@@ -133,26 +139,19 @@ fn reply_with_update_metadata<T: Serialize + Clone + Into<StatusCode>>(
 #[utoipa::path(
     post,
     path = "/update/status/error",
-    request_body = StatusQueryString,
     responses(
         (status = 200, description = "Status error response"),
         (status = 401, description = "Missing or invalid API key"),
+        (status = 403, description = "API key not granted the admin scope"),
         (status = 500, description = "Internal server error")
     ),
     tag = super::common::ADMIN_TAG
 )]
 async fn post_update_status_to_error(
     State(context): State<Arc<Context>>,
-    Json(query): Json<StatusQueryString>,
+    AdminClient(admin): AdminClient,
 ) -> Result<Response, Error> {
-    let api_key = match &context.api_key {
-        Some(key) => key,
-        None => return Err(Error::MissingApiKey),
-    };
-
-    if &query.api_key != api_key {
-        return Err(Error::ApiKey);
-    }
+    info!("[Admin] Update stopped by API client {}", admin.name);
 
     let connectors = context.builders.create();
 
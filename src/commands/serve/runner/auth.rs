@@ -0,0 +1,105 @@
+use super::common::Context;
+use super::error::Error;
+use crate::connectors::Connectors;
+use crate::models;
+use crate::models::api_client::common::{ApiClient, ApiScope};
+use crate::models::diffusion::DiffusionPolicy;
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderMap, header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+/// API client authenticated by the middleware, `None` for anonymous requests
+#[derive(Clone, Debug)]
+pub struct Client(pub Option<ApiClient>);
+
+impl Client {
+    /// Fail unless the request was made by a client granted `scope`
+    pub fn require(&self, scope: ApiScope) -> Result<&ApiClient, Error> {
+        match &self.0 {
+            Some(client) if client.has_scope(scope) => Ok(client),
+            Some(_) => Err(Error::MissingScope { scope }),
+            None => Err(Error::MissingApiKey),
+        }
+    }
+
+    /// Partially diffused data is only returned unmasked to clients granted
+    /// the full diffusion scope
+    pub fn diffusion_policy(&self) -> DiffusionPolicy {
+        match &self.0 {
+            Some(client) if client.has_scope(ApiScope::FullDiffusion) => DiffusionPolicy::Raw,
+            _ => DiffusionPolicy::Public,
+        }
+    }
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Resolve the active client owning `key`
+fn authenticate_key(
+    connectors: &Connectors,
+    key: &str,
+    scope: ApiScope,
+) -> Result<ApiClient, Error> {
+    let client = models::api_client::authenticate(connectors, key)?.ok_or(Error::InvalidApiKey)?;
+
+    if !client.has_scope(scope) {
+        return Err(Error::MissingScope { scope });
+    }
+
+    Ok(client)
+}
+
+/// Resolve the client of the `Authorization: Bearer` key, which must be granted the admin scope
+pub fn authenticate_admin(context: &Context, headers: &HeaderMap) -> Result<ApiClient, Error> {
+    let key = bearer_token(headers).ok_or(Error::MissingApiKey)?;
+
+    authenticate_key(&context.builders.create(), key, ApiScope::Admin)
+}
+
+/// API client granted the admin scope, shared by every `/admin` route
+#[derive(Clone, Debug)]
+pub struct AdminClient(pub ApiClient);
+
+impl FromRequestParts<Arc<Context>> for AdminClient {
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        context: &Arc<Context>,
+    ) -> Result<Self, Self::Rejection> {
+        authenticate_admin(context, &parts.headers).map(AdminClient)
+    }
+}
+
+/// Authenticate the `Authorization: Bearer` key of public requests, anonymous
+/// requests being accepted unless keys are required.
+pub async fn authenticate(
+    State(context): State<Arc<Context>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let client = match bearer_token(request.headers()) {
+        Some(key) => Some(authenticate_key(
+            &context.builders.create(),
+            key,
+            ApiScope::Public,
+        )?),
+        None if context.require_api_key => return Err(Error::MissingApiKey),
+        None => None,
+    };
+
+    request.extensions_mut().insert(Client(client));
+
+    Ok(next.run(request).await)
+}
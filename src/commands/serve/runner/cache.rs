@@ -0,0 +1,168 @@
+use super::error::Error;
+use crate::connectors::Connectors;
+use crate::models;
+use crate::models::diffusion::DiffusionPolicy;
+use axum::{
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, SubsecRound, Utc};
+
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+// Entities can change with any daily synchronisation, clients always revalidate
+const ENTITY_MAX_AGE: u32 = 0;
+
+/// Time of the last successful update, served data cannot be older
+pub fn last_update_timestamp(connectors: &Connectors) -> Result<Option<DateTime<Utc>>, Error> {
+    Ok(
+        models::update_metadata::last_success_update(connectors)?.map(|update| {
+            update
+                .finished_timestamp
+                .unwrap_or(update.launched_timestamp)
+        }),
+    )
+}
+
+/// Key of an entity response, entities described on a past date being
+/// distinct representations
+pub fn entity_key(id: &str, as_of: Option<NaiveDate>) -> String {
+    match as_of {
+        Some(as_of) => format!("{id}@{as_of}"),
+        None => id.to_string(),
+    }
+}
+
+/// Validators of an entity response, versioned by the processing date of the
+/// entity and by the last successful update.
+pub fn entity_validators(
+    key: &str,
+    date_dernier_traitement: Option<NaiveDateTime>,
+    last_update: Option<DateTime<Utc>>,
+) -> CacheValidators {
+    let date_dernier_traitement = date_dernier_traitement.map(|date| date.and_utc());
+    let tag = format!(
+        "{key}-{}",
+        date_dernier_traitement.map_or(0, |date| date.timestamp())
+    );
+
+    CacheValidators::new(
+        &tag,
+        date_dernier_traitement.max(last_update),
+        ENTITY_MAX_AGE,
+    )
+}
+
+/// HTTP validators of a response, used to answer conditional requests
+pub struct CacheValidators {
+    tag: String,
+    version: i64,
+    last_modified: Option<DateTime<Utc>>,
+    max_age: u32,
+    private: bool,
+    vary: Vec<HeaderName>,
+}
+
+impl CacheValidators {
+    pub fn new(tag: &str, last_modified: Option<DateTime<Utc>>, max_age: u32) -> Self {
+        CacheValidators {
+            tag: tag.to_string(),
+            version: last_modified.map_or(0, |date| date.timestamp()),
+            last_modified: last_modified.map(|date| date.trunc_subsecs(0)),
+            max_age,
+            private: false,
+            vary: Vec::new(),
+        }
+    }
+
+    fn etag(&self) -> String {
+        format!("\"{}-{}\"", self.tag, self.version)
+    }
+
+    /// Responses negotiated with the Accept header are distinct representations
+    pub fn with_format(mut self, geojson: bool) -> Self {
+        if geojson {
+            self.tag.push_str("-geojson");
+        }
+        self.vary.push(header::ACCEPT);
+        self
+    }
+
+    /// Masked and unmasked responses are distinct representations, unmasked
+    /// ones must not be stored by shared caches
+    pub fn with_policy(mut self, policy: DiffusionPolicy) -> Self {
+        self.tag.push_str(match policy {
+            DiffusionPolicy::Public => "-public",
+            DiffusionPolicy::Raw => "-full",
+        });
+        self.private = policy == DiffusionPolicy::Raw;
+        self.vary.push(header::AUTHORIZATION);
+        self
+    }
+
+    /// Whether the client copy is still valid, If-None-Match taking precedence
+    /// over If-Modified-Since as required by RFC 9110.
+    pub fn is_fresh(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+        {
+            return if_none_match
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == self.etag());
+        }
+
+        match (
+            self.last_modified,
+            headers
+                .get(header::IF_MODIFIED_SINCE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| DateTime::parse_from_rfc2822(value).ok()),
+        ) {
+            (Some(last_modified), Some(since)) => last_modified <= since,
+            _ => false,
+        }
+    }
+
+    pub fn not_modified(&self) -> Response {
+        self.apply(StatusCode::NOT_MODIFIED.into_response())
+    }
+
+    pub fn apply(&self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+
+        if let Ok(etag) = HeaderValue::from_str(&self.etag()) {
+            headers.insert(header::ETAG, etag);
+        }
+
+        if !self.vary.is_empty() {
+            let vary = self
+                .vary
+                .iter()
+                .map(HeaderName::as_str)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            if let Ok(vary) = HeaderValue::from_str(&vary) {
+                headers.insert(header::VARY, vary);
+            }
+        }
+
+        if let Some(last_modified) = self
+            .last_modified
+            .and_then(|date| HeaderValue::from_str(&date.format(HTTP_DATE_FORMAT).to_string()).ok())
+        {
+            headers.insert(header::LAST_MODIFIED, last_modified);
+        }
+
+        let visibility = if self.private { "private" } else { "public" };
+
+        if let Ok(cache_control) =
+            HeaderValue::from_str(&format!("{visibility}, max-age={}", self.max_age))
+        {
+            headers.insert(header::CACHE_CONTROL, cache_control);
+        }
+
+        response
+    }
+}
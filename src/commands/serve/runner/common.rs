@@ -1,33 +1,58 @@
+use super::rate_limit::RateLimiter;
+use super::sparse::Sparse;
+use super::status_token::StatusTokenSigner;
 use crate::connectors::ConnectorsBuilders;
-use crate::models::etablissement::common::Etablissement;
-use crate::models::lien_succession::common::LienSuccession;
-use crate::models::unite_legale::common::UniteLegale;
+use crate::models::diffusion::DiffusionPolicy;
+use crate::models::etablissement::common::{
+    Etablissement, EtablissementSearchParams, EtablissementSearchResultResponse,
+    EtablissementSortField, EtatAdministratif, ResponseFormat, SortDirection,
+};
+use crate::models::etablissement_periode::common::EtablissementPeriode;
+use crate::models::lien_succession::common::{
+    LienSuccession, LienSuccessionChainLink, SuccessionDirection,
+};
+use crate::models::nomenclature::LibelleIndex;
+use crate::models::nomenclature::common::{Coded, Libelles, NomenclatureName, Poste};
+use crate::models::unite_legale::common::{UniteLegale, UniteLegaleSuggestion};
+use crate::models::unite_legale_periode::common::UniteLegalePeriode;
 use crate::models::update_metadata::common::SyntheticGroupType;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
 
 pub const ADMIN_TAG: &str = "admin";
 pub const PUBLIC_TAG: &str = "public";
 
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+pub const DEFAULT_SUCCESSION_DEPTH: i32 = 10;
+pub const MAX_SUCCESSION_DEPTH: i32 = 50;
+
+pub const MIN_SUGGEST_LENGTH: usize = 2;
+pub const MAX_SUGGEST_LIMIT: i64 = 20;
+
 #[derive(Clone, Debug)]
 pub struct Context {
     pub builders: ConnectorsBuilders,
-    pub api_key: Option<String>,
+    pub require_api_key: bool,
     pub base_url: Option<String>,
+    pub rate_limiter: Arc<RateLimiter>,
+    pub status_token_signer: StatusTokenSigner,
 }
 
 #[derive(ToSchema, Deserialize)]
 pub struct UpdateOptions {
-    pub api_key: String,
     pub group_type: SyntheticGroupType,
     pub force: bool,
     pub asynchronous: bool,
 }
 
-#[derive(ToSchema, Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct StatusQueryString {
-    pub api_key: String,
+    /// Signed token given in the `Location` of an asynchronous update,
+    /// replacing the admin API key
+    pub token: Option<String>,
 }
 
 #[derive(ToSchema, Serialize)]
@@ -41,12 +66,103 @@ pub struct MetadataResponse {
     pub finished_timestamp: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UniteLegaleInclude {
+    Etablissements,
+    Siege,
+    LiensSuccession,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct UniteLegaleLookupParams {
+    /// Comma-separated fields of the legal unit to return, all by default
+    pub fields: Option<String>,
+    /// Comma-separated related objects to embed: `etablissements`, `siege`, `liens_succession`. Defaults to `siege`
+    pub include: Option<String>,
+    /// Date (YYYY-MM-DD) on which the legal unit and its siege are described, today by default
+    pub as_of: Option<NaiveDate>,
+    /// Add a `<field>_libelle` label next to each coded field, of embedded establishments too
+    pub labels: Option<bool>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct UniteLegaleEtablissementsParams {
+    pub etat_administratif: Option<EtatAdministratif>,
+    pub code_postal: Option<String>,
+    pub sort: Option<EtablissementSortField>,
+    pub direction: Option<SortDirection>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub cursor: Option<String>,
+    pub format: Option<ResponseFormat>,
+}
+
+impl UniteLegaleEtablissementsParams {
+    pub fn into_search_params(self, siren: String) -> EtablissementSearchParams {
+        EtablissementSearchParams {
+            siren: Some(siren),
+            etat_administratif: self.etat_administratif,
+            code_postal: self.code_postal,
+            sort: self.sort,
+            direction: self.direction,
+            limit: self.limit,
+            offset: self.offset,
+            cursor: self.cursor,
+            format: self.format,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct EtablissementsCounts {
+    /// Open establishments (etat_administratif A)
+    pub actifs: i64,
+    /// Closed establishments (etat_administratif F)
+    pub fermes: i64,
+}
+
+/// Entity followed by the labels of its codes when `labels=true` is requested
+#[derive(Serialize)]
+pub struct Labelled<T> {
+    #[serde(flatten)]
+    pub value: T,
+    #[serde(flatten)]
+    pub libelles: Option<Libelles>,
+}
+
+impl<T: Coded> Labelled<T> {
+    pub fn new(value: T) -> Self {
+        Labelled {
+            value,
+            libelles: None,
+        }
+    }
+
+    pub fn label(&mut self, index: &LibelleIndex) {
+        self.libelles = Some(index.libelles(&self.value));
+    }
+}
+
 #[derive(ToSchema, Serialize)]
 pub struct UniteLegaleInnerResponse {
     #[serde(flatten)]
-    pub unite_legale: UniteLegale,
-    pub etablissements: Vec<Etablissement>,
-    pub etablissement_siege: Etablissement,
+    #[schema(value_type = UniteLegale)]
+    pub unite_legale: Sparse<UniteLegale>,
+    #[serde(flatten)]
+    #[schema(ignore)]
+    pub libelles: Option<Libelles>,
+    pub etablissements_counts: EtablissementsCounts,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Etablissement>>)]
+    pub etablissements: Option<Vec<Labelled<Etablissement>>>,
+    /// `null` when the legal unit has no known siege
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Etablissement>)]
+    pub etablissement_siege: Option<Option<Labelled<Etablissement>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liens_succession: Option<Vec<LienSuccession>>,
 }
 
 #[derive(ToSchema, Serialize)]
@@ -54,21 +170,147 @@ pub struct EtablissementResponse {
     pub etablissement: EtablissementInnerResponse,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EtablissementInclude {
+    UniteLegale,
+    LiensSuccession,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct EtablissementLookupParams {
+    pub format: Option<ResponseFormat>,
+    /// Comma-separated fields of the establishment to return, all by default
+    pub fields: Option<String>,
+    /// Comma-separated related objects to embed: `unite_legale` (with its siege), `liens_succession`. Defaults to `unite_legale`
+    pub include: Option<String>,
+    /// Date (YYYY-MM-DD) on which the establishment and its legal unit are described, today by default
+    pub as_of: Option<NaiveDate>,
+    /// Add a `<field>_libelle` label next to each coded field, of the embedded legal unit too
+    pub labels: Option<bool>,
+}
+
 #[derive(ToSchema, Serialize)]
 pub struct EtablissementInnerResponse {
     #[serde(flatten)]
-    pub etablissement: Etablissement,
-    pub unite_legale: UniteLegaleEtablissementInnerResponse,
+    #[schema(value_type = Etablissement)]
+    pub etablissement: Sparse<Etablissement>,
+    #[serde(flatten)]
+    #[schema(ignore)]
+    pub libelles: Option<Libelles>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unite_legale: Option<UniteLegaleEtablissementInnerResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liens_succession: Option<Vec<LienSuccession>>,
 }
 
 #[derive(ToSchema, Serialize)]
 pub struct UniteLegaleEtablissementInnerResponse {
     #[serde(flatten)]
-    pub unite_legale: UniteLegale,
-    pub etablissement_siege: Etablissement,
+    #[schema(value_type = UniteLegale)]
+    pub unite_legale: Labelled<UniteLegale>,
+    /// `null` when the legal unit has no known siege
+    #[schema(value_type = Option<Etablissement>)]
+    pub etablissement_siege: Option<Labelled<Etablissement>>,
+}
+
+impl UniteLegaleEtablissementInnerResponse {
+    pub fn apply_diffusion(&mut self, policy: DiffusionPolicy) {
+        policy.apply(&mut self.unite_legale.value);
+        if let Some(ref mut etablissement_siege) = self.etablissement_siege {
+            policy.apply(&mut etablissement_siege.value);
+        }
+    }
 }
 
 #[derive(ToSchema, Serialize)]
 pub struct LiensSuccessionResponse {
     pub liens_succession: Vec<LienSuccession>,
 }
+
+#[derive(Deserialize, IntoParams)]
+pub struct SuccessionParams {
+    /// Side of the chain to follow, `both` by default
+    pub direction: Option<SuccessionDirection>,
+    /// Maximum number of links from the establishment, 10 by default, max 50
+    pub depth: Option<i32>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct SuccessionResponse {
+    /// Links of the chain, ordered by date
+    pub liens_succession: Vec<LienSuccessionChainLink>,
+    /// Establishments on the chain, in their order of appearance
+    pub etablissements: Vec<EtablissementSearchResultResponse>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct UniteLegalePeriodesResponse {
+    pub periodes: Vec<UniteLegalePeriode>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct EtablissementPeriodesResponse {
+    pub periodes: Vec<EtablissementPeriode>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct SuggestResponse {
+    pub suggestions: Vec<UniteLegaleSuggestion>,
+}
+
+#[derive(ToSchema, Deserialize)]
+pub struct UnitesLegalesBatchRequest {
+    pub sirens: Vec<String>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct UnitesLegalesBatchResponse {
+    pub unites_legales: Vec<UniteLegaleEtablissementInnerResponse>,
+    pub statuses: Vec<BatchItemStatus>,
+}
+
+#[derive(ToSchema, Deserialize)]
+pub struct EtablissementsBatchRequest {
+    pub sirets: Vec<String>,
+}
+
+#[derive(ToSchema, Deserialize)]
+pub struct EtablissementGeometrySearchRequest {
+    /// GeoJSON Polygon or MultiPolygon geometry
+    #[schema(value_type = Object)]
+    pub geometry: serde_json::Value,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct EtablissementsBatchResponse {
+    pub etablissements: Vec<EtablissementInnerResponse>,
+    pub statuses: Vec<BatchItemStatus>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct BatchItemStatus {
+    pub identifier: String,
+    pub status: BatchLookupStatus,
+}
+
+#[derive(ToSchema, Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchLookupStatus {
+    Found,
+    NotFound,
+    Invalid,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct NomenclatureParams {
+    /// Level of the entries to return, e.g. from 1 to 3 for `categories_juridiques`, all by default
+    pub niveau: Option<i16>,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct NomenclatureResponse {
+    pub nomenclature: NomenclatureName,
+    /// Entries of the nomenclature, ordered by code
+    pub postes: Vec<Poste>,
+}
@@ -1,5 +1,10 @@
 use crate::connectors::Error as ConnectorError;
-use crate::models::{etablissement, lien_succession, unite_legale, update_metadata};
+use crate::models::api_client::common::ApiScope;
+use crate::models::identifier::IdentifierError;
+use crate::models::{
+    api_client, etablissement, etablissement_periode, lien_succession, nomenclature, unite_legale,
+    unite_legale_periode, update_metadata,
+};
 use crate::update::error::Error as InternalUpdate;
 use axum::{
     Json,
@@ -12,9 +17,15 @@ use tracing::error;
 
 custom_error! { pub Error
     InvalidData = "Invalid data",
+    InvalidIdentifier{source: IdentifierError} = "Invalid identifier: {source}",
     InvalidSearchParams{message: String} = "Invalid search parameters: {message}",
-    MissingApiKey = "[Admin] Missing API key in configuration",
-    ApiKey = "[Admin] Wrong API key",
+    InvalidBatch{message: String} = "Invalid batch request: {message}",
+    InvalidLookupParams{message: String} = "Invalid lookup parameters: {message}",
+    MissingApiKey = "Missing API key, expected an Authorization: Bearer header",
+    InvalidApiKey = "Invalid, expired or revoked API key",
+    InvalidStatusToken = "[Admin] Invalid or expired status token",
+    MissingScope{scope: ApiScope} = "API key not granted the {scope} scope",
+    RateLimited{retry_after: u64} = "Too many requests, retry in {retry_after} seconds",
     MissingBaseUrlForAsync = "[Admin] No BASE_URL configured, needed for asynchronous updates",
     LocalConnectionFailed{source: r2d2::Error} = "Unable to connect to local database ({source}).",
     UpdateConnector {source: ConnectorError} = "[Update] Error while creating connector: {source}",
@@ -22,18 +33,32 @@ custom_error! { pub Error
     UniteLegale {source: unite_legale::error::Error} = "[UniteLegale] {source}",
     Etablissement {source: etablissement::error::Error} = "[Etablissement] {source}",
     LienSuccession {source: lien_succession::error::Error} = "[LienSuccession] {source}",
+    UniteLegalePeriode {source: unite_legale_periode::error::Error} = "[UniteLegalePeriode] {source}",
+    EtablissementPeriode {source: etablissement_periode::error::Error} = "[EtablissementPeriode] {source}",
+    Nomenclature {source: nomenclature::error::Error} = "[Nomenclature] {source}",
     Status {source: update_metadata::error::Error} = "[Status] {source}",
+    ApiClient {source: api_client::error::Error} = "[ApiClient] {source}",
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             Error::InvalidData => (StatusCode::BAD_REQUEST, self.to_string()),
+            Error::InvalidIdentifier { source: _ } => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::InvalidSearchParams { message: _ } => {
                 (StatusCode::BAD_REQUEST, self.to_string())
             }
+            Error::InvalidBatch { message: _ } => (StatusCode::BAD_REQUEST, self.to_string()),
+            Error::InvalidLookupParams { message: _ } => {
+                (StatusCode::BAD_REQUEST, self.to_string())
+            }
             Error::MissingApiKey => (StatusCode::UNAUTHORIZED, self.to_string()),
-            Error::ApiKey => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Error::InvalidApiKey => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Error::InvalidStatusToken => (StatusCode::UNAUTHORIZED, self.to_string()),
+            Error::MissingScope { scope: _ } => (StatusCode::FORBIDDEN, self.to_string()),
+            Error::RateLimited { retry_after: _ } => {
+                (StatusCode::TOO_MANY_REQUESTS, self.to_string())
+            }
             Error::MissingBaseUrlForAsync => (StatusCode::BAD_REQUEST, self.to_string()),
             Error::LocalConnectionFailed { source: _ } => {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
@@ -46,34 +71,68 @@ impl IntoResponse for Error {
                 unite_legale::error::Error::UniteLegaleNotFound => {
                     (StatusCode::NOT_FOUND, self.to_string())
                 }
+                unite_legale::error::Error::InvalidCursor => {
+                    (StatusCode::BAD_REQUEST, self.to_string())
+                }
+                unite_legale::error::Error::InvalidFacet { name: _ } => {
+                    (StatusCode::BAD_REQUEST, self.to_string())
+                }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             },
             Error::Etablissement { ref source } => match source {
                 etablissement::error::Error::EtablissementNotFound => {
                     (StatusCode::NOT_FOUND, self.to_string())
                 }
+                etablissement::error::Error::InvalidCursor => {
+                    (StatusCode::BAD_REQUEST, self.to_string())
+                }
+                etablissement::error::Error::InvalidFacet { name: _ } => {
+                    (StatusCode::BAD_REQUEST, self.to_string())
+                }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             },
             Error::LienSuccession { source: _ } => {
                 (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
             }
+            Error::UniteLegalePeriode { ref source } => match source {
+                unite_legale_periode::error::Error::NotExisting { date: _ } => {
+                    (StatusCode::NOT_FOUND, self.to_string())
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            },
+            Error::EtablissementPeriode { ref source } => match source {
+                etablissement_periode::error::Error::NotExisting { date: _ } => {
+                    (StatusCode::NOT_FOUND, self.to_string())
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            },
+            Error::Nomenclature { ref source } => match source {
+                nomenclature::error::Error::UnknownNomenclature { name: _ } => {
+                    (StatusCode::NOT_FOUND, self.to_string())
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            },
             Error::Status { ref source } => match source {
                 update_metadata::error::Error::MetadataNotFound => {
                     (StatusCode::NOT_FOUND, self.to_string())
                 }
                 _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             },
+            Error::ApiClient { source: _ } => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
         if status == StatusCode::INTERNAL_SERVER_ERROR {
             error!("[InternalServerError] {}", message);
         }
 
-        Json(ErrorResponse {
-            code: status.as_u16(),
-            message,
-        })
-        .into_response()
+        (
+            status,
+            Json(ErrorResponse {
+                code: status.as_u16(),
+                message,
+            }),
+        )
+            .into_response()
     }
 }
 
@@ -1,46 +1,212 @@
+use super::auth::Client;
+use super::cache::{CacheValidators, entity_key, entity_validators, last_update_timestamp};
 use super::common::{
-    Context, EtablissementInnerResponse, EtablissementResponse,
+    BatchItemStatus, BatchLookupStatus, Context, DEFAULT_SUCCESSION_DEPTH,
+    EtablissementGeometrySearchRequest, EtablissementInclude, EtablissementInnerResponse,
+    EtablissementLookupParams, EtablissementPeriodesResponse, EtablissementResponse,
+    EtablissementsBatchRequest, EtablissementsBatchResponse, Labelled, MAX_BATCH_SIZE,
+    MAX_SUCCESSION_DEPTH, SuccessionParams, SuccessionResponse,
     UniteLegaleEtablissementInnerResponse,
 };
 use super::error::Error;
+use super::export::{ExportFormat, stream_export};
+use super::geojson::{EtablissementFeatureCollection, Feature, geojson_response, wants_geojson};
+use super::sparse::{Sparse, parse_includes};
 use crate::models;
+use crate::models::api_client::common::ApiScope;
+use crate::models::diffusion::DiffusionPolicy;
 use crate::models::etablissement::common::{
     EtablissementSearchParams, EtablissementSearchResponse, EtablissementSearchResultResponse,
-    EtablissementSortField,
+    EtablissementSortField, is_valid_geometry, parse_bbox,
 };
+use crate::models::identifier::{parse_siren, parse_siret};
+use crate::models::lien_succession::common::SuccessionDirection;
+use crate::models::nomenclature::LibelleIndex;
+use crate::models::nomenclature::common::Coded;
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
 };
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::{Level, span};
 use utoipa_axum::{router::OpenApiRouter, routes};
 
+const MVT_CONTENT_TYPE: &str = "application/vnd.mapbox-vector-tile";
+// Tiles only change with updates, clients revalidate them hourly
+const TILE_MAX_AGE: u32 = 3600;
+
 /// Get establishment by SIRET
 #[utoipa::path(
     get,
     path = "/{siret}",
     params(
-        ("siret" = String, Path, description = "SIRET number")
+        ("siret" = String, Path, description = "SIRET number"),
+        EtablissementLookupParams
     ),
     responses(
-        (status = 200, description = "Etablissement response", body = EtablissementResponse),
-        (status = 400, description = "Invalid SIRET"),
-        (status = 404, description = "Etablissement not found")
+        (status = 200, description = "Etablissement response, as a GeoJSON feature if `format=geojson` or `Accept: application/geo+json` is sent", content(
+            (EtablissementResponse = "application/json"),
+            (Feature = "application/geo+json")
+        )),
+        (status = 304, description = "Etablissement not modified"),
+        (status = 400, description = "Invalid SIRET, fields, include or date"),
+        (status = 404, description = "Etablissement not found, or not existing on the `as_of` date")
     ),
     tag = super::common::PUBLIC_TAG
 )]
 async fn get_etablissement_by_siret(
     State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
     Path(siret): Path<String>,
-) -> Result<Json<EtablissementResponse>, Error> {
+    Query(params): Query<EtablissementLookupParams>,
+) -> Result<Response, Error> {
     let span = span!(Level::TRACE, "GET /etablissements");
     let _enter = span.enter();
 
-    if siret.len() != 14 {
-        return Err(Error::InvalidData);
+    let siret = parse_siret(&siret)?;
+
+    let include = parse_includes(
+        params.include.as_deref(),
+        &[EtablissementInclude::UniteLegale],
+    )
+    .map_err(|name| Error::InvalidLookupParams {
+        message: format!("unknown include {name}"),
+    })?;
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let policy = client.diffusion_policy();
+    let mut etablissement = models::etablissement::get(&mut connection, &siret)?;
+    if let Some(as_of) = params.as_of {
+        etablissement =
+            models::etablissement_periode::as_of(&mut connection, etablissement, as_of)?;
+    }
+    policy.apply(&mut etablissement);
+
+    let geojson = wants_geojson(&headers, params.format);
+    let validators = entity_validators(
+        &entity_key(&etablissement.siret, params.as_of),
+        etablissement.date_dernier_traitement,
+        last_update_timestamp(&connectors)?,
+    )
+    .with_policy(policy)
+    .with_format(geojson);
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
     }
 
+    let mut unite_legale = if include.contains(&EtablissementInclude::UniteLegale) {
+        let mut unite_legale = models::unite_legale::get(&mut connection, &etablissement.siren)?;
+        if let Some(as_of) = params.as_of {
+            unite_legale =
+                models::unite_legale_periode::as_of(&mut connection, unite_legale, as_of)?;
+        }
+
+        let mut unite_legale = UniteLegaleEtablissementInnerResponse {
+            etablissement_siege: super::unites_legales::get_siege(
+                &mut connection,
+                &unite_legale,
+                params.as_of,
+            )?
+            .map(Labelled::new),
+            unite_legale: Labelled::new(unite_legale),
+        };
+        unite_legale.apply_diffusion(policy);
+
+        Some(unite_legale)
+    } else {
+        None
+    };
+
+    let liens_succession = if include.contains(&EtablissementInclude::LiensSuccession) {
+        let mut liens_succession = models::lien_succession::get(&mut connection, &siret)?;
+        if let Some(as_of) = params.as_of {
+            liens_succession.retain(|lien| lien.date_lien_succession <= as_of);
+        }
+
+        Some(liens_succession)
+    } else {
+        None
+    };
+
+    let libelles = if params.labels.unwrap_or(false) {
+        let mut coded: Vec<&dyn Coded> = vec![&etablissement];
+        if let Some(ref unite_legale) = unite_legale {
+            coded.push(&unite_legale.unite_legale.value);
+            if let Some(ref etablissement_siege) = unite_legale.etablissement_siege {
+                coded.push(&etablissement_siege.value);
+            }
+        }
+        let index = LibelleIndex::load(&mut connection, coded)?;
+
+        if let Some(ref mut unite_legale) = unite_legale {
+            unite_legale.unite_legale.label(&index);
+            if let Some(ref mut etablissement_siege) = unite_legale.etablissement_siege {
+                etablissement_siege.label(&index);
+            }
+        }
+
+        Some(index.libelles(&etablissement))
+    } else {
+        None
+    };
+
+    let etablissement =
+        Sparse::new(etablissement, params.fields.as_deref(), "siret").map_err(|name| {
+            Error::InvalidLookupParams {
+                message: format!("unknown field {name}"),
+            }
+        })?;
+
+    let etablissement = EtablissementInnerResponse {
+        libelles: libelles.map(|libelles| etablissement.select_libelles(libelles)),
+        etablissement,
+        unite_legale,
+        liens_succession,
+    };
+
+    if geojson {
+        return Ok(validators.apply(geojson_response(Feature::from(&etablissement))));
+    }
+
+    Ok(validators.apply(Json(EtablissementResponse { etablissement }).into_response()))
+}
+
+/// List the periods of an establishment, the most recent first
+#[utoipa::path(
+    get,
+    path = "/{siret}/periodes",
+    params(
+        ("siret" = String, Path, description = "SIRET number")
+    ),
+    responses(
+        (status = 200, description = "Periods of the establishment", body = EtablissementPeriodesResponse),
+        (status = 304, description = "Periods not modified"),
+        (status = 400, description = "Invalid SIRET"),
+        (status = 404, description = "Etablissement not found")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_etablissement_periodes(
+    State(context): State<Arc<Context>>,
+    headers: HeaderMap,
+    Path(siret): Path<String>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /etablissements/periodes");
+    let _enter = span.enter();
+
+    let siret = parse_siret(&siret)?;
+
     let connectors = context.builders.create();
     let mut connection = connectors
         .local
@@ -48,20 +214,242 @@ async fn get_etablissement_by_siret(
         .get()
         .map_err(|e| Error::LocalConnectionFailed { source: e })?;
 
+    // Periods only hold names and activities, never masked by the diffusion
     let etablissement = models::etablissement::get(&mut connection, &siret)?;
-    let unite_legale = models::unite_legale::get(&mut connection, &etablissement.siren)?;
-    let etablissement_siege =
-        models::etablissement::get_siege_with_siren(&mut connection, &etablissement.siren)?;
-
-    Ok(Json(EtablissementResponse {
-        etablissement: EtablissementInnerResponse {
-            etablissement,
-            unite_legale: UniteLegaleEtablissementInnerResponse {
-                unite_legale,
-                etablissement_siege,
+
+    let validators = entity_validators(
+        &etablissement.siret,
+        etablissement.date_dernier_traitement,
+        last_update_timestamp(&connectors)?,
+    );
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let periodes = models::etablissement_periode::get(&mut connection, &siret)?;
+
+    Ok(validators.apply(Json(EtablissementPeriodesResponse { periodes }).into_response()))
+}
+
+/// Follow the succession chain of an establishment
+#[utoipa::path(
+    get,
+    path = "/{siret}/succession",
+    params(
+        ("siret" = String, Path, description = "SIRET number"),
+        SuccessionParams
+    ),
+    responses(
+        (status = 200, description = "Succession chain", body = SuccessionResponse),
+        (status = 304, description = "Succession chain not modified"),
+        (status = 400, description = "Invalid SIRET or depth")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_etablissement_succession(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Path(siret): Path<String>,
+    Query(params): Query<SuccessionParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /etablissements/succession");
+    let _enter = span.enter();
+
+    let siret = parse_siret(&siret)?;
+
+    let depth = params.depth.unwrap_or(DEFAULT_SUCCESSION_DEPTH);
+    if !(1..=MAX_SUCCESSION_DEPTH).contains(&depth) {
+        return Err(Error::InvalidLookupParams {
+            message: format!("depth must be between 1 and {MAX_SUCCESSION_DEPTH}"),
+        });
+    }
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let liens_succession = models::lien_succession::get_chain(
+        &mut connection,
+        &siret,
+        params.direction.unwrap_or(SuccessionDirection::Both),
+        depth,
+    )?;
+
+    let policy = client.diffusion_policy();
+    let validators = entity_validators(
+        &siret,
+        liens_succession
+            .iter()
+            .filter_map(|lien| lien.date_dernier_traitement_lien_succession)
+            .max(),
+        last_update_timestamp(&connectors)?,
+    )
+    .with_policy(policy);
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let mut sirets = vec![siret.clone()];
+    for lien in &liens_succession {
+        for siret in [
+            &lien.siret_etablissement_predecesseur,
+            &lien.siret_etablissement_successeur,
+        ] {
+            if !sirets.contains(siret) {
+                sirets.push(siret.clone());
+            }
+        }
+    }
+
+    let mut found: HashMap<String, _> = models::etablissement::get_batch(&mut connection, &sirets)?
+        .into_iter()
+        .map(|etablissement| (etablissement.siret.clone(), etablissement))
+        .collect();
+
+    let mut etablissements: Vec<EtablissementSearchResultResponse> = sirets
+        .iter()
+        .filter_map(|siret| found.remove(siret))
+        .map(EtablissementSearchResultResponse::from)
+        .collect();
+    policy.apply_all(&mut etablissements);
+
+    Ok(validators.apply(
+        Json(SuccessionResponse {
+            liens_succession,
+            etablissements,
+        })
+        .into_response(),
+    ))
+}
+
+/// Get several establishments by SIRET
+#[utoipa::path(
+    post,
+    path = "/batch",
+    request_body = EtablissementsBatchRequest,
+    responses(
+        (status = 200, description = "Batch response", body = EtablissementsBatchResponse),
+        (status = 400, description = "Invalid batch request")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn post_etablissements_batch(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<EtablissementsBatchRequest>,
+) -> Result<Json<EtablissementsBatchResponse>, Error> {
+    let span = span!(Level::TRACE, "POST /etablissements/batch");
+    let _enter = span.enter();
+
+    if request.sirets.len() > MAX_BATCH_SIZE {
+        return Err(Error::InvalidBatch {
+            message: format!("at most {MAX_BATCH_SIZE} sirets are allowed"),
+        });
+    }
+
+    // Identifiers are deduplicated once normalized, invalid ones as given
+    let mut seen = HashSet::new();
+    let requested: Vec<(String, Option<String>)> = request
+        .sirets
+        .into_iter()
+        .map(|siret| {
+            let parsed = parse_siret(&siret).ok();
+            (siret, parsed)
+        })
+        .filter(|(siret, parsed)| seen.insert(parsed.as_ref().unwrap_or(siret).clone()))
+        .collect();
+
+    let sirets: Vec<String> = requested
+        .iter()
+        .filter_map(|(_, parsed)| parsed.clone())
+        .collect();
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let policy = client.diffusion_policy();
+    let mut etablissements = models::etablissement::get_batch(&mut connection, &sirets)?;
+    policy.apply_all(&mut etablissements);
+
+    let sirens: Vec<String> = etablissements
+        .iter()
+        .map(|e| e.siren.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    let unites_legales: HashMap<String, _> =
+        models::unite_legale::get_batch(&mut connection, &sirens)?
+            .into_iter()
+            .map(|mut u| {
+                policy.apply(&mut u);
+                (u.siren.clone(), u)
+            })
+            .collect();
+    let etablissements_sieges: HashMap<String, _> =
+        models::etablissement::get_sieges_with_sirens(&mut connection, &sirens)?
+            .into_iter()
+            .map(|mut e| {
+                policy.apply(&mut e);
+                (e.siren.clone(), e)
+            })
+            .collect();
+
+    let mut found: HashMap<String, EtablissementInnerResponse> = etablissements
+        .into_iter()
+        .filter_map(|etablissement| {
+            let unite_legale = unites_legales.get(&etablissement.siren)?.clone();
+            let etablissement_siege = etablissements_sieges.get(&etablissement.siren).cloned();
+
+            Some((
+                etablissement.siret.clone(),
+                EtablissementInnerResponse {
+                    etablissement: Sparse::full(etablissement),
+                    libelles: None,
+                    unite_legale: Some(UniteLegaleEtablissementInnerResponse {
+                        unite_legale: Labelled::new(unite_legale),
+                        etablissement_siege: etablissement_siege.map(Labelled::new),
+                    }),
+                    liens_succession: None,
+                },
+            ))
+        })
+        .collect();
+
+    let mut response = EtablissementsBatchResponse {
+        etablissements: Vec::with_capacity(found.len()),
+        statuses: Vec::with_capacity(requested.len()),
+    };
+
+    for (siret, parsed) in requested {
+        let status = match parsed {
+            None => BatchLookupStatus::Invalid,
+            Some(parsed) => match found.remove(&parsed) {
+                Some(etablissement) => {
+                    response.etablissements.push(etablissement);
+                    BatchLookupStatus::Found
+                }
+                None => BatchLookupStatus::NotFound,
             },
-        },
-    }))
+        };
+
+        response.statuses.push(BatchItemStatus {
+            identifier: siret,
+            status,
+        });
+    }
+
+    Ok(Json(response))
 }
 
 /// Search establishments
@@ -70,40 +458,89 @@ async fn get_etablissement_by_siret(
     path = "/",
     params(EtablissementSearchParams),
     responses(
-        (status = 200, description = "Search results", body = EtablissementSearchResponse),
+        (status = 200, description = "Search results, as a GeoJSON feature collection if `format=geojson` or `Accept: application/geo+json` is sent", content(
+            (EtablissementSearchResponse = "application/json"),
+            (EtablissementFeatureCollection = "application/geo+json")
+        )),
         (status = 400, description = "Invalid search parameters")
     ),
     tag = super::common::PUBLIC_TAG
 )]
 async fn search_etablissements(
     State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
     Query(params): Query<EtablissementSearchParams>,
-) -> Result<Json<EtablissementSearchResponse>, Error> {
+) -> Result<Response, Error> {
     let span = span!(Level::TRACE, "GET /etablissements (search)");
     let _enter = span.enter();
 
-    // Validate geographic params: all-or-none
-    let has_any_geo = params.lat.is_some() || params.lng.is_some() || params.radius.is_some();
-    let has_all_geo = params.lat.is_some() && params.lng.is_some() && params.radius.is_some();
-    if has_any_geo && !has_all_geo {
+    search(&context, &headers, params, client.diffusion_policy())
+}
+
+/// Search establishments within a polygon
+#[utoipa::path(
+    post,
+    path = "/search",
+    params(EtablissementSearchParams),
+    request_body = EtablissementGeometrySearchRequest,
+    responses(
+        (status = 200, description = "Search results, as a GeoJSON feature collection if `format=geojson` or `Accept: application/geo+json` is sent", content(
+            (EtablissementSearchResponse = "application/json"),
+            (EtablissementFeatureCollection = "application/geo+json")
+        )),
+        (status = 400, description = "Invalid search parameters or geometry")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn post_search_etablissements(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Query(mut params): Query<EtablissementSearchParams>,
+    Json(request): Json<EtablissementGeometrySearchRequest>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "POST /etablissements/search");
+    let _enter = span.enter();
+
+    if !is_valid_geometry(&request.geometry) {
         return Err(Error::InvalidSearchParams {
-            message: "lat, lng, and radius must all be provided together".to_string(),
+            message: "geometry must be a GeoJSON Polygon or MultiPolygon of closed rings"
+                .to_string(),
         });
     }
 
-    // Validate sort constraints
-    match params.sort {
-        Some(EtablissementSortField::Distance) if !has_all_geo => {
+    params.geometry = Some(request.geometry.to_string());
+
+    search(&context, &headers, params, client.diffusion_policy())
+}
+
+pub(super) fn search(
+    context: &Context,
+    headers: &HeaderMap,
+    mut params: EtablissementSearchParams,
+    policy: DiffusionPolicy,
+) -> Result<Response, Error> {
+    validate_search_params(&mut params)?;
+
+    // Validate cursor constraints
+    if params.cursor.is_some() {
+        if params.offset.is_some() {
             return Err(Error::InvalidSearchParams {
-                message: "sort=distance requires lat, lng, and radius parameters".to_string(),
+                message: "cursor and offset cannot be used together".to_string(),
             });
         }
-        Some(EtablissementSortField::Relevance) if params.q.is_none() => {
+
+        let relevance_sort = match params.sort {
+            Some(sort) => sort == EtablissementSortField::Relevance,
+            None => params.q.is_some(),
+        };
+
+        if relevance_sort {
             return Err(Error::InvalidSearchParams {
-                message: "sort=relevance requires a q parameter".to_string(),
+                message: "cursor cannot be used with sort=relevance".to_string(),
             });
         }
-        _ => {}
     }
 
     let connectors = context.builders.create();
@@ -113,7 +550,7 @@ async fn search_etablissements(
         .get()
         .map_err(|e| Error::LocalConnectionFailed { source: e })?;
 
-    let output = models::etablissement::search(&mut connection, &params)?;
+    let output = models::etablissement::search(&mut connection, &params, policy)?;
 
     let total = output
         .results
@@ -125,38 +562,198 @@ async fn search_etablissements(
         })
         .unwrap_or(0);
 
-    Ok(Json(EtablissementSearchResponse {
-        etablissements: output
-            .results
-            .into_iter()
-            .map(|r| EtablissementSearchResultResponse {
-                siret: r.siret,
-                siren: r.siren,
-                etat_administratif: r.etat_administratif,
-                date_creation: r.date_creation,
-                denomination_usuelle: r.denomination_usuelle,
-                enseigne_1: r.enseigne_1,
-                enseigne_2: r.enseigne_2,
-                enseigne_3: r.enseigne_3,
-                code_postal: r.code_postal,
-                libelle_commune: r.libelle_commune,
-                activite_principale: r.activite_principale,
-                etablissement_siege: r.etablissement_siege,
-                position: r.position,
-                meter_distance: r.meter_distance,
-                score: r.score,
-            })
-            .collect(),
+    let mut etablissements: Vec<_> = output
+        .results
+        .into_iter()
+        .map(EtablissementSearchResultResponse::from)
+        .collect();
+    policy.apply_all(&mut etablissements);
+
+    let response = EtablissementSearchResponse {
+        etablissements,
         total,
         limit: output.limit,
         offset: output.offset,
         sort: output.sort,
         direction: output.direction,
-    }))
+        next_cursor: output.next_cursor,
+        facets: output.facets,
+    };
+
+    if wants_geojson(headers, params.format) {
+        return Ok(geojson_response(EtablissementFeatureCollection::from(
+            response,
+        )));
+    }
+
+    Ok(Json(response).into_response())
+}
+
+/// Export all establishments matching a search
+#[utoipa::path(
+    get,
+    path = "/export",
+    params(EtablissementSearchParams),
+    responses(
+        (status = 200, description = "Every matching establishment, streamed as CSV if `Accept: text/csv` is sent, NDJSON otherwise"),
+        (status = 400, description = "Invalid search parameters"),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 403, description = "API key not granted the export scope")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn export_etablissements(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Query(mut params): Query<EtablissementSearchParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /etablissements/export");
+    let _enter = span.enter();
+
+    client.require(ApiScope::Export)?;
+    validate_search_params(&mut params)?;
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let policy = client.diffusion_policy();
+
+    Ok(stream_export(
+        ExportFormat::from_headers(&headers),
+        move |on_batch| {
+            models::etablissement::export(&mut connection, &params, policy, |results| {
+                let mut results: Vec<_> = results
+                    .into_iter()
+                    .map(EtablissementSearchResultResponse::from)
+                    .collect();
+                policy.apply_all(&mut results);
+
+                on_batch(results)
+            })
+            .map_err(|e| e.into())
+        },
+    ))
+}
+
+/// Get a Mapbox Vector Tile of establishments
+#[utoipa::path(
+    get,
+    path = "/tiles/{z}/{x}/{y}",
+    params(
+        ("z" = u32, Path, description = "Zoom level"),
+        ("x" = u32, Path, description = "Tile column"),
+        ("y" = String, Path, description = "Tile row followed by `.mvt`"),
+        EtablissementSearchParams
+    ),
+    responses(
+        (status = 200, description = "Vector tile with an `etablissements` layer, made of points carrying a `count` property below zoom 12", content_type = "application/vnd.mapbox-vector-tile"),
+        (status = 304, description = "Tile not modified since the last successful update"),
+        (status = 400, description = "Invalid tile coordinates or search parameters")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_etablissements_tile(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Path((z, x, y)): Path<(u32, u32, String)>,
+    Query(mut params): Query<EtablissementSearchParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /etablissements/tiles");
+    let _enter = span.enter();
+
+    let y = y
+        .strip_suffix(".mvt")
+        .and_then(|y| y.parse::<u32>().ok())
+        .ok_or(Error::InvalidData)?;
+
+    if z > models::etablissement::TILE_MAX_ZOOM || x >= 1 << z || y >= 1 << z {
+        return Err(Error::InvalidData);
+    }
+
+    validate_search_params(&mut params)?;
+
+    let connectors = context.builders.create();
+    let policy = client.diffusion_policy();
+    let tag = match policy {
+        DiffusionPolicy::Public => "tile",
+        DiffusionPolicy::Raw => "tile-raw",
+    };
+
+    let validators = CacheValidators::new(tag, last_update_timestamp(&connectors)?, TILE_MAX_AGE)
+        .with_policy(policy);
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let tile = models::etablissement::tile(&mut connection, &params, z, x, y, policy)?;
+
+    Ok(validators.apply(([(header::CONTENT_TYPE, MVT_CONTENT_TYPE)], tile).into_response()))
+}
+
+/// Validate the search parameters, normalizing the identifiers
+fn validate_search_params(params: &mut EtablissementSearchParams) -> Result<(), Error> {
+    if let Some(siren) = params.siren.as_deref() {
+        params.siren = Some(parse_siren(siren)?);
+    }
+
+    // Validate geographic params: all-or-none
+    let has_any_geo = params.lat.is_some() || params.lng.is_some() || params.radius.is_some();
+    let has_all_geo = params.lat.is_some() && params.lng.is_some() && params.radius.is_some();
+    if has_any_geo && !has_all_geo {
+        return Err(Error::InvalidSearchParams {
+            message: "lat, lng, and radius must all be provided together".to_string(),
+        });
+    }
+
+    if params
+        .bbox
+        .as_deref()
+        .is_some_and(|bbox| parse_bbox(bbox).is_none())
+    {
+        return Err(Error::InvalidSearchParams {
+            message: "bbox must be minLng,minLat,maxLng,maxLat".to_string(),
+        });
+    }
+
+    // Validate sort constraints
+    match params.sort {
+        Some(EtablissementSortField::Distance) if !has_all_geo => {
+            return Err(Error::InvalidSearchParams {
+                message: "sort=distance requires lat, lng, and radius parameters".to_string(),
+            });
+        }
+        Some(EtablissementSortField::Relevance) if params.q.is_none() => {
+            return Err(Error::InvalidSearchParams {
+                message: "sort=relevance requires a q parameter".to_string(),
+            });
+        }
+        _ => {}
+    }
+
+    Ok(())
 }
 
 pub fn router() -> OpenApiRouter<Arc<Context>> {
     OpenApiRouter::new()
         .routes(routes!(get_etablissement_by_siret))
+        .routes(routes!(get_etablissement_periodes))
+        .routes(routes!(get_etablissement_succession))
         .routes(routes!(search_etablissements))
+        .routes(routes!(post_search_etablissements))
+        .routes(routes!(post_etablissements_batch))
+        .routes(routes!(export_etablissements))
+        .routes(routes!(get_etablissements_tile))
 }
@@ -0,0 +1,213 @@
+use super::error::Error;
+use crate::models::etablissement::common::EtablissementSearchResultResponse;
+use crate::models::unite_legale::common::UniteLegaleSearchResultResponse;
+use axum::{
+    body::Body,
+    http::{HeaderMap, HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::error;
+
+// Batches waiting to be sent to the client before the database side blocks
+const EXPORT_CHANNEL_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        if accept.contains("text/csv") {
+            ExportFormat::Csv
+        } else {
+            ExportFormat::Ndjson
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn encode<R: ExportRow>(&self, rows: Vec<R>) -> Vec<u8> {
+        let mut buffer = Vec::new();
+
+        for row in rows {
+            match self {
+                ExportFormat::Csv => buffer.extend(csv_line(row.csv_record()).into_bytes()),
+                ExportFormat::Ndjson => {
+                    if serde_json::to_writer(&mut buffer, &row).is_ok() {
+                        buffer.push(b'\n');
+                    }
+                }
+            }
+        }
+
+        buffer
+    }
+}
+
+pub trait ExportRow: Serialize + Send + 'static {
+    fn csv_header() -> &'static [&'static str];
+    fn csv_record(self) -> Vec<Option<String>>;
+}
+
+/// Stream rows produced by a blocking `export` function, batch by batch, as
+/// the response body. Errors happening after the headers were sent abort the
+/// response.
+pub fn stream_export<R, F>(format: ExportFormat, export: F) -> Response
+where
+    R: ExportRow,
+    F: FnOnce(&mut dyn FnMut(Vec<R>) -> bool) -> Result<(), Error> + Send + 'static,
+{
+    let (sender, mut receiver) =
+        mpsc::channel::<Result<Vec<u8>, std::io::Error>>(EXPORT_CHANNEL_SIZE);
+
+    tokio::task::spawn_blocking(move || {
+        if let ExportFormat::Csv = format {
+            let header = R::csv_header()
+                .iter()
+                .map(|column| Some(column.to_string()))
+                .collect();
+
+            if sender
+                .blocking_send(Ok(csv_line(header).into_bytes()))
+                .is_err()
+            {
+                return;
+            }
+        }
+
+        let result = export(&mut |rows| sender.blocking_send(Ok(format.encode(rows))).is_ok());
+
+        if let Err(export_error) = result {
+            error!("[InternalServerError] {}", export_error);
+            let _ = sender.blocking_send(Err(std::io::Error::other(export_error.to_string())));
+        }
+    });
+
+    let stream = futures::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+
+    (
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(format.content_type()),
+        )],
+        Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+fn csv_line(fields: Vec<Option<String>>) -> String {
+    let mut line = fields
+        .into_iter()
+        .map(|field| {
+            let field = field.unwrap_or_default();
+
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+
+    line.push('\n');
+    line
+}
+
+impl ExportRow for EtablissementSearchResultResponse {
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "siret",
+            "siren",
+            "statut_diffusion",
+            "etat_administratif",
+            "date_creation",
+            "denomination_usuelle",
+            "enseigne_1",
+            "enseigne_2",
+            "enseigne_3",
+            "code_postal",
+            "libelle_commune",
+            "code_departement",
+            "code_region",
+            "activite_principale",
+            "etablissement_siege",
+            "longitude",
+            "latitude",
+            "meter_distance",
+            "score",
+        ]
+    }
+
+    fn csv_record(self) -> Vec<Option<String>> {
+        vec![
+            Some(self.siret),
+            Some(self.siren),
+            Some(self.statut_diffusion),
+            Some(self.etat_administratif),
+            self.date_creation.map(|d| d.to_string()),
+            self.denomination_usuelle,
+            self.enseigne_1,
+            self.enseigne_2,
+            self.enseigne_3,
+            self.code_postal,
+            self.libelle_commune,
+            self.code_departement,
+            self.code_region,
+            self.activite_principale,
+            Some(self.etablissement_siege.to_string()),
+            self.position.as_ref().map(|p| p.x.to_string()),
+            self.position.as_ref().map(|p| p.y.to_string()),
+            self.meter_distance.map(|d| d.to_string()),
+            self.score.map(|s| s.to_string()),
+        ]
+    }
+}
+
+impl ExportRow for UniteLegaleSearchResultResponse {
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "siren",
+            "etat_administratif",
+            "date_creation",
+            "denomination",
+            "denomination_usuelle_1",
+            "denomination_usuelle_2",
+            "denomination_usuelle_3",
+            "activite_principale",
+            "categorie_juridique",
+            "categorie_entreprise",
+            "score",
+        ]
+    }
+
+    fn csv_record(self) -> Vec<Option<String>> {
+        vec![
+            Some(self.siren),
+            Some(self.etat_administratif),
+            self.date_creation.map(|d| d.to_string()),
+            self.denomination,
+            self.denomination_usuelle_1,
+            self.denomination_usuelle_2,
+            self.denomination_usuelle_3,
+            self.activite_principale,
+            self.categorie_juridique,
+            self.categorie_entreprise,
+            self.score.map(|s| s.to_string()),
+        ]
+    }
+}
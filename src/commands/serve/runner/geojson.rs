@@ -0,0 +1,138 @@
+use super::common::EtablissementInnerResponse;
+use crate::models::etablissement::common::{
+    EtablissementSearchResponse, EtablissementSearchResultResponse, ResponseFormat,
+};
+use crate::models::search_facet::FacetBucket;
+use axum::{
+    Json,
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+};
+use postgis_diesel::types::Point;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+pub const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+/// `format` takes precedence over the Accept header
+pub fn wants_geojson(headers: &HeaderMap, format: Option<ResponseFormat>) -> bool {
+    match format {
+        Some(ResponseFormat::Geojson) => true,
+        Some(ResponseFormat::Json) => false,
+        None => headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|accept| accept.contains(GEOJSON_CONTENT_TYPE)),
+    }
+}
+
+pub fn geojson_response<T: Serialize>(body: T) -> Response {
+    ([(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)], Json(body)).into_response()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub enum GeometryType {
+    Point,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Geometry {
+    #[serde(rename = "type")]
+    pub kind: GeometryType,
+    /// Longitude and latitude (WGS 84)
+    pub coordinates: [f64; 2],
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub enum FeatureType {
+    Feature,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Feature {
+    #[serde(rename = "type")]
+    pub kind: FeatureType,
+    pub id: String,
+    pub geometry: Option<Geometry>,
+    #[schema(value_type = Object)]
+    pub properties: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Feature {
+    /// Build a feature located at `position`, which is removed from the
+    /// properties since it is given by the geometry.
+    fn new<P: Serialize>(id: String, position: Option<&Point>, properties: &P) -> Self {
+        let properties = match serde_json::to_value(properties) {
+            Ok(serde_json::Value::Object(mut properties)) => {
+                properties.remove("position");
+                properties
+            }
+            _ => serde_json::Map::new(),
+        };
+
+        Feature {
+            kind: FeatureType::Feature,
+            id,
+            geometry: position.map(|p| Geometry {
+                kind: GeometryType::Point,
+                coordinates: [p.x, p.y],
+            }),
+            properties,
+        }
+    }
+}
+
+/// The geometry is only given when `position` is among the selected fields
+impl From<&EtablissementInnerResponse> for Feature {
+    fn from(response: &EtablissementInnerResponse) -> Self {
+        Feature::new(
+            response.etablissement.siret.clone(),
+            response
+                .etablissement
+                .position
+                .as_ref()
+                .filter(|_| response.etablissement.contains("position")),
+            response,
+        )
+    }
+}
+
+impl From<&EtablissementSearchResultResponse> for Feature {
+    fn from(result: &EtablissementSearchResultResponse) -> Self {
+        Feature::new(result.siret.clone(), result.position.as_ref(), result)
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub enum FeatureCollectionType {
+    FeatureCollection,
+}
+
+/// Search results as GeoJSON, pagination is kept as foreign members
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EtablissementFeatureCollection {
+    #[serde(rename = "type")]
+    pub kind: FeatureCollectionType,
+    pub features: Vec<Feature>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<BTreeMap<String, Vec<FacetBucket>>>,
+}
+
+impl From<EtablissementSearchResponse> for EtablissementFeatureCollection {
+    fn from(response: EtablissementSearchResponse) -> Self {
+        EtablissementFeatureCollection {
+            kind: FeatureCollectionType::FeatureCollection,
+            features: response.etablissements.iter().map(Feature::from).collect(),
+            total: response.total,
+            limit: response.limit,
+            offset: response.offset,
+            next_cursor: response.next_cursor,
+            facets: response.facets,
+        }
+    }
+}
@@ -1,9 +1,13 @@
+use super::cache::{entity_validators, last_update_timestamp};
 use super::common::{Context, LiensSuccessionResponse};
 use super::error::Error;
 use crate::models;
+use crate::models::identifier::parse_siret;
 use axum::{
     Json,
     extract::{Path, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
 };
 use std::sync::Arc;
 use tracing::{Level, span};
@@ -18,20 +22,20 @@ use utoipa_axum::{router::OpenApiRouter, routes};
     ),
     responses(
         (status = 200, description = "LiensSuccession response", body = LiensSuccessionResponse),
+        (status = 304, description = "LiensSuccession not modified"),
         (status = 400, description = "Invalid SIRET")
     ),
     tag = super::common::PUBLIC_TAG
 )]
 async fn get_liens_succession_by_siret(
     State(context): State<Arc<Context>>,
+    headers: HeaderMap,
     Path(siret): Path<String>,
-) -> Result<Json<LiensSuccessionResponse>, Error> {
+) -> Result<Response, Error> {
     let span = span!(Level::TRACE, "GET /etablissements/liens_succession");
     let _enter = span.enter();
 
-    if siret.len() != 14 {
-        return Err(Error::InvalidData);
-    }
+    let siret = parse_siret(&siret)?;
 
     let connectors = context.builders.create();
     let mut connection = connectors
@@ -42,7 +46,20 @@ async fn get_liens_succession_by_siret(
 
     let liens_succession = models::lien_succession::get(&mut connection, &siret)?;
 
-    Ok(Json(LiensSuccessionResponse { liens_succession }))
+    let validators = entity_validators(
+        &siret,
+        liens_succession
+            .iter()
+            .filter_map(|lien| lien.date_dernier_traitement_lien_succession)
+            .max(),
+        last_update_timestamp(&connectors)?,
+    );
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    Ok(validators.apply(Json(LiensSuccessionResponse { liens_succession }).into_response()))
 }
 
 pub fn router() -> OpenApiRouter<Arc<Context>> {
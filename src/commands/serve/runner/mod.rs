@@ -1,13 +1,26 @@
 mod admin;
+mod auth;
+mod cache;
 mod error;
 mod etablissements;
+mod export;
+mod geojson;
 mod liens_succession;
+mod nomenclatures;
+mod rate_limit;
 mod root;
+mod sparse;
+mod status_token;
+mod suggest;
 mod unites_legales;
 
 pub mod common;
 
+pub use rate_limit::{RateLimitConfig, RateLimiter};
+pub use status_token::StatusTokenSigner;
+
 use axum::http::{Method, header};
+use axum::middleware;
 use common::Context;
 use sentry::integrations::tower::NewSentryLayer;
 use std::net::SocketAddr;
@@ -35,14 +48,28 @@ struct ApiDoc;
 pub async fn run(addr: SocketAddr, context: Context) {
     let shared_context = Arc::new(context);
 
-    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .nest("/admin", admin::router())
+    let public_router = OpenApiRouter::new()
         .nest("/v3/etablissements", etablissements::router())
         .nest(
             "/v3/etablissements/liens_succession",
             liens_succession::router(),
         )
         .nest("/v3/unites_legales", unites_legales::router())
+        .nest("/v3/nomenclatures", nomenclatures::router())
+        .nest("/v3/suggest", suggest::router())
+        // Limits are checked first, rejected requests never reach the database
+        .route_layer(middleware::from_fn_with_state(
+            shared_context.clone(),
+            auth::authenticate,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            shared_context.clone(),
+            rate_limit::limit,
+        ));
+
+    let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .nest("/admin", admin::router())
+        .merge(public_router)
         .merge(root::router())
         .split_for_parts();
 
@@ -50,7 +77,13 @@ pub async fn run(addr: SocketAddr, context: Context) {
         .layer(
             tower_http::cors::CorsLayer::new()
                 .allow_methods([Method::GET, Method::POST])
-                .allow_headers([header::CONTENT_TYPE])
+                .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                .expose_headers([
+                    header::RETRY_AFTER,
+                    rate_limit::RATE_LIMIT_LIMIT,
+                    rate_limit::RATE_LIMIT_REMAINING,
+                    rate_limit::RATE_LIMIT_RESET,
+                ])
                 .allow_origin(tower_http::cors::Any),
         )
         .merge(Scalar::with_url("/scalar", api))
@@ -60,7 +93,7 @@ pub async fn run(addr: SocketAddr, context: Context) {
 
     axum::serve(
         tokio::net::TcpListener::bind(&addr).await.unwrap(),
-        app.into_make_service(),
+        app.into_make_service_with_connect_info::<SocketAddr>(),
     )
     .await
     .unwrap();
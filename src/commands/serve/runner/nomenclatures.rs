@@ -0,0 +1,72 @@
+use super::cache::{entity_validators, last_update_timestamp};
+use super::common::{Context, NomenclatureParams, NomenclatureResponse};
+use super::error::Error;
+use crate::models;
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::{Level, span};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+/// Get the entries of a nomenclature
+#[utoipa::path(
+    get,
+    path = "/{name}",
+    params(
+        ("name" = String, Path, description = "Nomenclature: `naf_rev2`, `naf25`, `categories_juridiques`, `tranches_effectifs` or `types_voie`"),
+        NomenclatureParams
+    ),
+    responses(
+        (status = 200, description = "Nomenclature response", body = NomenclatureResponse),
+        (status = 304, description = "Nomenclature not modified"),
+        (status = 404, description = "Unknown nomenclature")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_nomenclature(
+    State(context): State<Arc<Context>>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(params): Query<NomenclatureParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /nomenclatures");
+    let _enter = span.enter();
+
+    let nomenclature = models::nomenclature::parse_name(&name)?;
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    // Nomenclatures only change with updates
+    let validators = entity_validators(
+        nomenclature.as_str(),
+        None,
+        last_update_timestamp(&connectors)?,
+    );
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let postes = models::nomenclature::get(&mut connection, nomenclature, params.niveau)?;
+
+    Ok(validators.apply(
+        Json(NomenclatureResponse {
+            nomenclature,
+            postes,
+        })
+        .into_response(),
+    ))
+}
+
+pub fn router() -> OpenApiRouter<Arc<Context>> {
+    OpenApiRouter::new().routes(routes!(get_nomenclature))
+}
@@ -0,0 +1,326 @@
+use super::auth::bearer_token;
+use super::common::Context;
+use super::error::Error;
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+pub const RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+pub const RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+pub const RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+// Routes counted in daily quotas, as matched by the router
+const EXPENSIVE_ROUTES: [&str; 9] = [
+    "/v3/etablissements",
+    "/v3/etablissements/search",
+    "/v3/etablissements/export",
+    "/v3/etablissements/batch",
+    "/v3/etablissements/tiles/{z}/{x}/{y}",
+    "/v3/unites_legales",
+    "/v3/unites_legales/export",
+    "/v3/unites_legales/batch",
+    "/v3/unites_legales/{siren}/etablissements",
+];
+
+// Beyond this number of tracked callers, idle ones are forgotten
+const MAX_TRACKED_CALLERS: usize = 10_000;
+
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Requests per minute for each API key, 0 disables the limit
+    pub key_per_minute: u32,
+    /// Requests per minute for each anonymous IP, 0 disables the limit
+    pub ip_per_minute: u32,
+    /// Daily searches, exports, batches and tiles for each API key, 0 disables the quota
+    pub key_daily_quota: u32,
+    /// Daily searches, exports, batches and tiles for each anonymous IP, 0 disables the quota
+    pub ip_daily_quota: u32,
+    /// Read the caller IP from the last `X-Forwarded-For` address
+    pub trust_forwarded_for: bool,
+}
+
+/// Callers are identified before authentication, by the digest of their key
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Caller {
+    ApiKey([u8; 32]),
+    Ip(IpAddr),
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug)]
+struct Quota {
+    day: NaiveDate,
+    used: u32,
+}
+
+/// Current state of the limit applied to a request
+struct Usage {
+    limit: u32,
+    remaining: u32,
+    reset: u64,
+}
+
+enum Decision {
+    Allowed(Usage),
+    Limited(Usage),
+}
+
+/// In memory counters, each instance of the service limiting on its own
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<Caller, Bucket>>,
+    quotas: Mutex<HashMap<Caller, Quota>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            quotas: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn per_minute(&self, caller: Caller) -> u32 {
+        match caller {
+            Caller::ApiKey(_) => self.config.key_per_minute,
+            Caller::Ip(_) => self.config.ip_per_minute,
+        }
+    }
+
+    fn daily_quota(&self, caller: Caller) -> u32 {
+        match caller {
+            Caller::ApiKey(_) => self.config.key_daily_quota,
+            Caller::Ip(_) => self.config.ip_daily_quota,
+        }
+    }
+
+    /// Take a token from the bucket of `caller`, holding `per_minute` tokens
+    /// and refilled continuously.
+    fn take_token(&self, caller: Caller) -> Option<Decision> {
+        self.take_tokens(caller, 1.0)
+    }
+
+    /// Whether the bucket of `caller` is empty, without taking a token
+    fn is_exhausted(&self, caller: Caller) -> Option<Usage> {
+        match self.take_tokens(caller, 0.0) {
+            Some(Decision::Limited(usage)) => Some(usage),
+            _ => None,
+        }
+    }
+
+    fn take_tokens(&self, caller: Caller, count: f64) -> Option<Decision> {
+        let capacity = self.per_minute(caller);
+
+        if capacity == 0 {
+            return None;
+        }
+
+        let capacity = f64::from(capacity);
+        let refill_rate = capacity / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= MAX_TRACKED_CALLERS {
+            // Forget the buckets which are full again
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.refilled_at).as_secs_f64() * refill_rate
+                    < capacity
+            });
+        }
+
+        let bucket = buckets.entry(caller).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.refilled_at).as_secs_f64() * refill_rate)
+            .min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            return Some(Decision::Limited(Usage {
+                limit: capacity as u32,
+                remaining: 0,
+                reset: ((1.0 - bucket.tokens) / refill_rate).ceil() as u64,
+            }));
+        }
+
+        bucket.tokens -= count;
+
+        Some(Decision::Allowed(Usage {
+            limit: capacity as u32,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((capacity - bucket.tokens) / refill_rate).ceil() as u64,
+        }))
+    }
+
+    /// Count an expensive request in the daily quota of `caller`, reset at
+    /// midnight UTC.
+    fn use_quota(&self, caller: Caller) -> Option<Decision> {
+        let limit = self.daily_quota(caller);
+
+        if limit == 0 {
+            return None;
+        }
+
+        let now = Utc::now();
+        let today = now.date_naive();
+        let reset = today
+            .succ_opt()
+            .and_then(|tomorrow| tomorrow.and_hms_opt(0, 0, 0))
+            .map_or(0, |midnight| {
+                (midnight.and_utc() - now).num_seconds().max(0) as u64
+            });
+
+        let mut quotas = self.quotas.lock().unwrap_or_else(|e| e.into_inner());
+
+        if quotas.len() >= MAX_TRACKED_CALLERS {
+            quotas.retain(|_, quota| quota.day == today);
+        }
+
+        let quota = quotas.entry(caller).or_insert(Quota {
+            day: today,
+            used: 0,
+        });
+
+        if quota.day != today {
+            quota.day = today;
+            quota.used = 0;
+        }
+
+        if quota.used >= limit {
+            return Some(Decision::Limited(Usage {
+                limit,
+                remaining: 0,
+                reset,
+            }));
+        }
+
+        quota.used += 1;
+
+        Some(Decision::Allowed(Usage {
+            limit,
+            remaining: limit - quota.used,
+            reset,
+        }))
+    }
+}
+
+fn caller_ip(request: &Request, trust_forwarded_for: bool) -> Option<IpAddr> {
+    if trust_forwarded_for
+        && let Some(ip) = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok())
+    {
+        return Some(ip);
+    }
+
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+fn insert_usage(headers: &mut HeaderMap, usage: &Usage) {
+    headers.insert(RATE_LIMIT_LIMIT, HeaderValue::from(usage.limit));
+    headers.insert(RATE_LIMIT_REMAINING, HeaderValue::from(usage.remaining));
+    headers.insert(RATE_LIMIT_RESET, HeaderValue::from(usage.reset));
+}
+
+fn too_many_requests(usage: &Usage) -> Response {
+    let mut response = Error::RateLimited {
+        retry_after: usage.reset,
+    }
+    .into_response();
+
+    insert_usage(response.headers_mut(), usage);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(usage.reset));
+
+    response
+}
+
+/// Limit the request rate of API clients by key and of anonymous callers by
+/// IP, searches and exports being also counted in daily quotas. Requests
+/// rejected for an invalid key are counted against their IP, and keys are not
+/// checked anymore once it is limited.
+pub async fn limit(State(context): State<Arc<Context>>, request: Request, next: Next) -> Response {
+    let limiter = &context.rate_limiter;
+
+    let ip = caller_ip(&request, limiter.config.trust_forwarded_for);
+    let caller = match bearer_token(request.headers()) {
+        Some(key) => Some(Caller::ApiKey(Sha256::digest(key.as_bytes()).into())),
+        None => ip.map(Caller::Ip),
+    };
+
+    let failed_ip = match caller {
+        Some(Caller::ApiKey(_)) => ip,
+        _ => None,
+    };
+
+    if let Some(usage) = failed_ip.and_then(|ip| limiter.is_exhausted(Caller::Ip(ip))) {
+        return too_many_requests(&usage);
+    }
+
+    let Some(caller) = caller else {
+        return next.run(request).await;
+    };
+
+    let expensive = request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|path| EXPENSIVE_ROUTES.contains(&path.as_str().trim_end_matches('/')));
+
+    let rate = match limiter.take_token(caller) {
+        Some(Decision::Limited(usage)) => return too_many_requests(&usage),
+        Some(Decision::Allowed(usage)) => Some(usage),
+        None => None,
+    };
+
+    let quota = match expensive.then(|| limiter.use_quota(caller)).flatten() {
+        Some(Decision::Limited(usage)) => return too_many_requests(&usage),
+        Some(Decision::Allowed(usage)) => Some(usage),
+        None => None,
+    };
+
+    let mut response = next.run(request).await;
+
+    if let Some(ip) = failed_ip
+        && response.status() == StatusCode::UNAUTHORIZED
+    {
+        limiter.take_token(Caller::Ip(ip));
+    }
+
+    // The closest limit is reported when both apply
+    if let Some(usage) = [rate, quota]
+        .into_iter()
+        .flatten()
+        .min_by_key(|usage| usage.remaining)
+    {
+        insert_usage(response.headers_mut(), &usage);
+    }
+
+    response
+}
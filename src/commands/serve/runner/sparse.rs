@@ -0,0 +1,114 @@
+use crate::models::nomenclature::common::Libelles;
+use serde::{Serialize, Serializer, de::DeserializeOwned, ser::Error as _};
+use std::ops::Deref;
+
+/// Entity serialized with only the fields requested with `fields=`, its
+/// identifier being always kept.
+pub struct Sparse<T> {
+    value: T,
+    fields: Option<Vec<String>>,
+}
+
+impl<T: Serialize> Sparse<T> {
+    pub fn full(value: T) -> Self {
+        Sparse {
+            value,
+            fields: None,
+        }
+    }
+
+    /// Restrict `value` to `fields`, returning the first unknown field on failure
+    pub fn new(value: T, fields: Option<&str>, key: &str) -> Result<Self, String> {
+        let Some(fields) = fields else {
+            return Ok(Sparse::full(value));
+        };
+
+        let known = match serde_json::to_value(&value) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+
+        let mut selected = vec![key.to_string()];
+
+        for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            if !known.contains_key(field) {
+                return Err(field.to_string());
+            }
+
+            if !selected.iter().any(|s| s == field) {
+                selected.push(field.to_string());
+            }
+        }
+
+        Ok(Sparse {
+            value,
+            fields: Some(selected),
+        })
+    }
+}
+
+impl<T> Sparse<T> {
+    /// Whether `field` is returned
+    pub fn contains(&self, field: &str) -> bool {
+        self.fields
+            .as_ref()
+            .is_none_or(|fields| fields.iter().any(|selected| selected == field))
+    }
+
+    /// Keep the `<field>_libelle` labels of the returned fields only
+    pub fn select_libelles(&self, mut libelles: Libelles) -> Libelles {
+        libelles.retain(|name, _| {
+            name.strip_suffix("_libelle")
+                .is_none_or(|field| self.contains(field))
+        });
+        libelles
+    }
+}
+
+impl<T> Deref for Sparse<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Serialize> Serialize for Sparse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(ref fields) = self.fields else {
+            return self.value.serialize(serializer);
+        };
+
+        match serde_json::to_value(&self.value).map_err(S::Error::custom)? {
+            serde_json::Value::Object(mut map) => {
+                map.retain(|name, _| fields.contains(name));
+                map.serialize(serializer)
+            }
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+/// Parse the comma separated related objects of `include=`, `default` being
+/// used when the parameter is missing. Returns the first unknown name on failure.
+pub fn parse_includes<I>(include: Option<&str>, default: &[I]) -> Result<Vec<I>, String>
+where
+    I: DeserializeOwned + PartialEq + Copy,
+{
+    let Some(include) = include else {
+        return Ok(default.to_vec());
+    };
+
+    let mut parsed = Vec::new();
+
+    for name in include.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let value: I = serde_json::from_value(serde_json::Value::String(name.to_string()))
+            .map_err(|_| name.to_string())?;
+
+        if !parsed.contains(&value) {
+            parsed.push(value);
+        }
+    }
+
+    Ok(parsed)
+}
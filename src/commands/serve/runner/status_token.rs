@@ -0,0 +1,76 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+// Tokens are not renewed by polling, keys are needed to follow longer updates
+const STATUS_TOKEN_LIFETIME_MINUTES: i64 = 15;
+const STATUS_TOKEN_PURPOSE: &str = "update_status";
+
+/// Signs the short-lived tokens given in the polling `Location` of updates,
+/// which only allow reading the update status.
+#[derive(Clone)]
+pub struct StatusTokenSigner {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for StatusTokenSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StatusTokenSigner").finish_non_exhaustive()
+    }
+}
+
+impl StatusTokenSigner {
+    /// Without a configured secret, a random one is used and tokens are only
+    /// valid on this instance until it restarts.
+    pub fn new(secret: Option<String>) -> Self {
+        let secret = match secret {
+            Some(secret) => secret.into_bytes(),
+            None => {
+                let mut secret = vec![0u8; 32];
+                rand::rng().fill_bytes(&mut secret);
+                secret
+            }
+        };
+
+        StatusTokenSigner { secret }
+    }
+
+    fn mac(&self, payload: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    pub fn sign(&self) -> String {
+        let expires_at =
+            (Utc::now() + Duration::minutes(STATUS_TOKEN_LIFETIME_MINUTES)).timestamp();
+        let payload = format!("{STATUS_TOKEN_PURPOSE}.{expires_at}");
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(&payload).finalize().into_bytes());
+
+        format!("{payload}.{signature}")
+    }
+
+    pub fn verify(&self, token: &str) -> bool {
+        let Some((payload, signature)) = token.rsplit_once('.') else {
+            return false;
+        };
+
+        let Ok(signature) = URL_SAFE_NO_PAD.decode(signature) else {
+            return false;
+        };
+
+        if self.mac(payload).verify_slice(&signature).is_err() {
+            return false;
+        }
+
+        match payload.split_once('.') {
+            Some((STATUS_TOKEN_PURPOSE, expires_at)) => expires_at
+                .parse::<i64>()
+                .is_ok_and(|expires_at| expires_at > Utc::now().timestamp()),
+            _ => false,
+        }
+    }
+}
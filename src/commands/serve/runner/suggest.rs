@@ -0,0 +1,57 @@
+use super::common::{Context, MAX_SUGGEST_LIMIT, MIN_SUGGEST_LENGTH, SuggestResponse};
+use super::error::Error;
+use crate::models;
+use crate::models::unite_legale::common::UniteLegaleSuggestParams;
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use std::sync::Arc;
+use tracing::{Level, span};
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+/// Suggest legal units from the first letters of their name
+#[utoipa::path(
+    get,
+    path = "/",
+    params(UniteLegaleSuggestParams),
+    responses(
+        (status = 200, description = "Suggestions", body = SuggestResponse),
+        (status = 400, description = "Query too short")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_suggestions(
+    State(context): State<Arc<Context>>,
+    Query(params): Query<UniteLegaleSuggestParams>,
+) -> Result<Json<SuggestResponse>, Error> {
+    let span = span!(Level::TRACE, "GET /suggest");
+    let _enter = span.enter();
+
+    let q = params.q.trim();
+
+    if q.chars().count() < MIN_SUGGEST_LENGTH {
+        return Err(Error::InvalidSearchParams {
+            message: format!("q must contain at least {MIN_SUGGEST_LENGTH} characters"),
+        });
+    }
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let suggestions = models::unite_legale::suggest(
+        &mut connection,
+        q,
+        params.limit.unwrap_or(10).clamp(1, MAX_SUGGEST_LIMIT),
+    )?;
+
+    Ok(Json(SuggestResponse { suggestions }))
+}
+
+pub fn router() -> OpenApiRouter<Arc<Context>> {
+    OpenApiRouter::new().routes(routes!(get_suggestions))
+}
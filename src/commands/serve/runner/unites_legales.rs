@@ -1,14 +1,37 @@
-use super::common::{Context, UniteLegaleInnerResponse, UniteLegaleResponse};
+use super::auth::Client;
+use super::cache::{entity_key, entity_validators, last_update_timestamp};
+use super::common::{
+    BatchItemStatus, BatchLookupStatus, Context, EtablissementsCounts, Labelled, MAX_BATCH_SIZE,
+    UniteLegaleEtablissementInnerResponse, UniteLegaleEtablissementsParams, UniteLegaleInclude,
+    UniteLegaleInnerResponse, UniteLegaleLookupParams, UniteLegalePeriodesResponse,
+    UniteLegaleResponse, UnitesLegalesBatchRequest, UnitesLegalesBatchResponse,
+};
 use super::error::Error;
+use super::export::{ExportFormat, stream_export};
+use super::geojson::EtablissementFeatureCollection;
+use super::sparse::{Sparse, parse_includes};
+use crate::connectors::local::Connection;
 use crate::models;
+use crate::models::api_client::common::ApiScope;
+use crate::models::diffusion::DiffusionPolicy;
+use crate::models::etablissement::common::{Etablissement, EtablissementSearchResponse};
+use crate::models::etablissement::error::Error as EtablissementError;
+use crate::models::etablissement_periode::error::Error as EtablissementPeriodeError;
+use crate::models::identifier::{parse_numero_tva, parse_siren};
+use crate::models::nomenclature::LibelleIndex;
+use crate::models::nomenclature::common::Coded;
 use crate::models::unite_legale::common::{
-    UniteLegaleSearchParams, UniteLegaleSearchResponse, UniteLegaleSearchResultResponse,
-    UniteLegaleSortField,
+    UniteLegale, UniteLegaleSearchParams, UniteLegaleSearchResponse,
+    UniteLegaleSearchResultResponse, UniteLegaleSortField,
 };
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
 };
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::{Level, span};
 use utoipa_axum::{router::OpenApiRouter, routes};
@@ -18,25 +41,83 @@ use utoipa_axum::{router::OpenApiRouter, routes};
     get,
     path = "/{siren}",
     params(
-        ("siren" = String, Path, description = "SIREN number")
+        ("siren" = String, Path, description = "SIREN number"),
+        UniteLegaleLookupParams
     ),
     responses(
         (status = 200, description = "UniteLegale response", body = UniteLegaleResponse),
-        (status = 400, description = "Invalid SIREN"),
-        (status = 404, description = "UniteLegale not found")
+        (status = 304, description = "UniteLegale not modified"),
+        (status = 400, description = "Invalid SIREN, fields, include or date"),
+        (status = 404, description = "UniteLegale not found, or not existing on the `as_of` date")
     ),
     tag = super::common::PUBLIC_TAG
 )]
 async fn get_unite_legale_by_siren(
     State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
     Path(siren): Path<String>,
-) -> Result<Json<UniteLegaleResponse>, Error> {
+    Query(params): Query<UniteLegaleLookupParams>,
+) -> Result<Response, Error> {
     let span = span!(Level::TRACE, "GET /unites_legales");
     let _enter = span.enter();
 
-    if siren.len() != 9 {
-        return Err(Error::InvalidData);
-    }
+    lookup(
+        &context,
+        &headers,
+        parse_siren(&siren)?,
+        params,
+        client.diffusion_policy(),
+    )
+}
+
+/// Get unit legale by intra-community VAT number
+#[utoipa::path(
+    get,
+    path = "/tva/{numero}",
+    params(
+        ("numero" = String, Path, description = "French intra-community VAT number, e.g. FR32123456789"),
+        UniteLegaleLookupParams
+    ),
+    responses(
+        (status = 200, description = "UniteLegale response", body = UniteLegaleResponse),
+        (status = 304, description = "UniteLegale not modified"),
+        (status = 400, description = "Invalid VAT number, fields, include or date"),
+        (status = 404, description = "UniteLegale not found, or not existing on the `as_of` date")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_unite_legale_by_numero_tva(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Path(numero): Path<String>,
+    Query(params): Query<UniteLegaleLookupParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /unites_legales/tva");
+    let _enter = span.enter();
+
+    lookup(
+        &context,
+        &headers,
+        parse_numero_tva(&numero)?,
+        params,
+        client.diffusion_policy(),
+    )
+}
+
+fn lookup(
+    context: &Context,
+    headers: &HeaderMap,
+    siren: String,
+    params: UniteLegaleLookupParams,
+    policy: DiffusionPolicy,
+) -> Result<Response, Error> {
+    let include = parse_includes(params.include.as_deref(), &[UniteLegaleInclude::Siege]).map_err(
+        |name| Error::InvalidLookupParams {
+            message: format!("unknown include {name}"),
+        },
+    )?;
 
     let connectors = context.builders.create();
     let mut connection = connectors
@@ -45,18 +126,355 @@ async fn get_unite_legale_by_siren(
         .get()
         .map_err(|e| Error::LocalConnectionFailed { source: e })?;
 
-    let unite_legale = models::unite_legale::get(&mut connection, &siren)?;
-    let etablissements = models::etablissement::get_with_siren(&mut connection, &siren)?;
-    let etablissement_siege =
-        models::etablissement::get_siege_with_siren(&mut connection, &unite_legale.siren)?;
+    let mut unite_legale = models::unite_legale::get(&mut connection, &siren)?;
+    if let Some(as_of) = params.as_of {
+        unite_legale = models::unite_legale_periode::as_of(&mut connection, unite_legale, as_of)?;
+    }
+    policy.apply(&mut unite_legale);
+
+    let validators = entity_validators(
+        &entity_key(&unite_legale.siren, params.as_of),
+        unite_legale.date_dernier_traitement,
+        last_update_timestamp(&connectors)?,
+    )
+    .with_policy(policy);
+
+    if validators.is_fresh(headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let mut etablissements_counts = EtablissementsCounts {
+        actifs: 0,
+        fermes: 0,
+    };
+    let counts = match params.as_of {
+        Some(as_of) => {
+            models::etablissement_periode::count_with_siren_as_of(&mut connection, &siren, as_of)?
+        }
+        None => models::etablissement::count_with_siren(&mut connection, &siren)?,
+    };
+    for (etat_administratif, count) in counts {
+        match etat_administratif.as_str() {
+            "A" => etablissements_counts.actifs += count,
+            "F" => etablissements_counts.fermes += count,
+            _ => {}
+        }
+    }
+
+    let mut etablissements = if include.contains(&UniteLegaleInclude::Etablissements) {
+        let mut etablissements = models::etablissement::get_with_siren(&mut connection, &siren)?;
+        if let Some(as_of) = params.as_of {
+            etablissements =
+                models::etablissement_periode::all_as_of(&mut connection, etablissements, as_of)?;
+        }
+        policy.apply_all(&mut etablissements);
+
+        Some(
+            etablissements
+                .into_iter()
+                .map(Labelled::new)
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        None
+    };
+
+    let mut etablissement_siege = if include.contains(&UniteLegaleInclude::Siege) {
+        let mut etablissement_siege = get_siege(&mut connection, &unite_legale, params.as_of)?;
+        if let Some(ref mut etablissement_siege) = etablissement_siege {
+            policy.apply(etablissement_siege);
+        }
+
+        Some(etablissement_siege.map(Labelled::new))
+    } else {
+        None
+    };
+
+    let liens_succession = if include.contains(&UniteLegaleInclude::LiensSuccession) {
+        let mut liens_succession =
+            models::lien_succession::get_with_siren(&mut connection, &siren)?;
+        if let Some(as_of) = params.as_of {
+            liens_succession.retain(|lien| lien.date_lien_succession <= as_of);
+        }
+
+        Some(liens_succession)
+    } else {
+        None
+    };
+
+    let libelles = if params.labels.unwrap_or(false) {
+        let coded = std::iter::once(&unite_legale as &dyn Coded)
+            .chain(
+                etablissements
+                    .iter()
+                    .flatten()
+                    .map(|e| &e.value as &dyn Coded),
+            )
+            .chain(
+                etablissement_siege
+                    .iter()
+                    .flatten()
+                    .map(|e| &e.value as &dyn Coded),
+            );
+        let index = LibelleIndex::load(&mut connection, coded)?;
+
+        for etablissement in etablissements
+            .iter_mut()
+            .flatten()
+            .chain(etablissement_siege.iter_mut().flatten())
+        {
+            etablissement.label(&index);
+        }
+
+        Some(index.libelles(&unite_legale))
+    } else {
+        None
+    };
+
+    let unite_legale =
+        Sparse::new(unite_legale, params.fields.as_deref(), "siren").map_err(|name| {
+            Error::InvalidLookupParams {
+                message: format!("unknown field {name}"),
+            }
+        })?;
 
-    Ok(Json(UniteLegaleResponse {
+    let response = Json(UniteLegaleResponse {
         unite_legale: UniteLegaleInnerResponse {
+            libelles: libelles.map(|libelles| unite_legale.select_libelles(libelles)),
             unite_legale,
+            etablissements_counts,
             etablissements,
             etablissement_siege,
+            liens_succession,
         },
-    }))
+    });
+
+    Ok(validators.apply(response.into_response()))
+}
+
+/// Siege of a legal unit, as it was on `as_of` when given, `None` when the
+/// legal unit has no known siege
+pub fn get_siege(
+    connection: &mut Connection,
+    unite_legale: &UniteLegale,
+    as_of: Option<NaiveDate>,
+) -> Result<Option<Etablissement>, Error> {
+    let siege = match (as_of, &unite_legale.nic_siege) {
+        (Some(_), Some(nic_siege)) => {
+            let siret = format!("{}{nic_siege}", unite_legale.siren);
+            models::etablissement::get(connection, &siret)
+        }
+        _ => models::etablissement::get_siege_with_siren(connection, &unite_legale.siren),
+    };
+
+    let siege = match siege {
+        Ok(siege) => siege,
+        Err(EtablissementError::EtablissementNotFound) => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+
+    match as_of {
+        Some(as_of) => match models::etablissement_periode::as_of(connection, siege, as_of) {
+            Ok(siege) => Ok(Some(siege)),
+            Err(EtablissementPeriodeError::NotExisting { date: _ }) => Ok(None),
+            Err(error) => Err(error.into()),
+        },
+        None => Ok(Some(siege)),
+    }
+}
+
+/// List the establishments of a legal unit
+#[utoipa::path(
+    get,
+    path = "/{siren}/etablissements",
+    params(
+        ("siren" = String, Path, description = "SIREN number"),
+        UniteLegaleEtablissementsParams
+    ),
+    responses(
+        (status = 200, description = "Establishments of the legal unit, as a GeoJSON feature collection if `format=geojson` or `Accept: application/geo+json` is sent", content(
+            (EtablissementSearchResponse = "application/json"),
+            (EtablissementFeatureCollection = "application/geo+json")
+        )),
+        (status = 400, description = "Invalid SIREN or parameters")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_unite_legale_etablissements(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Path(siren): Path<String>,
+    Query(params): Query<UniteLegaleEtablissementsParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /unites_legales/etablissements");
+    let _enter = span.enter();
+
+    let siren = parse_siren(&siren)?;
+
+    super::etablissements::search(
+        &context,
+        &headers,
+        params.into_search_params(siren),
+        client.diffusion_policy(),
+    )
+}
+
+/// List the periods of a legal unit, the most recent first
+#[utoipa::path(
+    get,
+    path = "/{siren}/periodes",
+    params(
+        ("siren" = String, Path, description = "SIREN number")
+    ),
+    responses(
+        (status = 200, description = "Periods of the legal unit", body = UniteLegalePeriodesResponse),
+        (status = 304, description = "Periods not modified"),
+        (status = 400, description = "Invalid SIREN"),
+        (status = 404, description = "UniteLegale not found")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn get_unite_legale_periodes(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Path(siren): Path<String>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /unites_legales/periodes");
+    let _enter = span.enter();
+
+    let siren = parse_siren(&siren)?;
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let policy = client.diffusion_policy();
+    let unite_legale = models::unite_legale::get(&mut connection, &siren)?;
+
+    let validators = entity_validators(
+        &unite_legale.siren,
+        unite_legale.date_dernier_traitement,
+        last_update_timestamp(&connectors)?,
+    )
+    .with_policy(policy);
+
+    if validators.is_fresh(&headers) {
+        return Ok(validators.not_modified());
+    }
+
+    let mut periodes = models::unite_legale_periode::get(&mut connection, &siren)?;
+    policy.apply_periodes(&unite_legale, &mut periodes);
+
+    Ok(validators.apply(Json(UniteLegalePeriodesResponse { periodes }).into_response()))
+}
+
+/// Get several legal units by SIREN
+#[utoipa::path(
+    post,
+    path = "/batch",
+    request_body = UnitesLegalesBatchRequest,
+    responses(
+        (status = 200, description = "Batch response", body = UnitesLegalesBatchResponse),
+        (status = 400, description = "Invalid batch request")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn post_unites_legales_batch(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    Json(request): Json<UnitesLegalesBatchRequest>,
+) -> Result<Json<UnitesLegalesBatchResponse>, Error> {
+    let span = span!(Level::TRACE, "POST /unites_legales/batch");
+    let _enter = span.enter();
+
+    if request.sirens.len() > MAX_BATCH_SIZE {
+        return Err(Error::InvalidBatch {
+            message: format!("at most {MAX_BATCH_SIZE} sirens are allowed"),
+        });
+    }
+
+    // Identifiers are deduplicated once normalized, invalid ones as given
+    let mut seen = HashSet::new();
+    let requested: Vec<(String, Option<String>)> = request
+        .sirens
+        .into_iter()
+        .map(|siren| {
+            let parsed = parse_siren(&siren).ok();
+            (siren, parsed)
+        })
+        .filter(|(siren, parsed)| seen.insert(parsed.as_ref().unwrap_or(siren).clone()))
+        .collect();
+
+    let sirens: Vec<String> = requested
+        .iter()
+        .filter_map(|(_, parsed)| parsed.clone())
+        .collect();
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    let policy = client.diffusion_policy();
+    let mut unites_legales = models::unite_legale::get_batch(&mut connection, &sirens)?;
+    policy.apply_all(&mut unites_legales);
+
+    let mut etablissements_sieges: HashMap<String, _> =
+        models::etablissement::get_sieges_with_sirens(&mut connection, &sirens)?
+            .into_iter()
+            .map(|mut e| {
+                policy.apply(&mut e);
+                (e.siren.clone(), e)
+            })
+            .collect();
+
+    // Legal units without a known siege are found too
+    let mut found: HashMap<String, UniteLegaleEtablissementInnerResponse> = unites_legales
+        .into_iter()
+        .map(|unite_legale| {
+            let etablissement_siege = etablissements_sieges.remove(&unite_legale.siren);
+
+            (
+                unite_legale.siren.clone(),
+                UniteLegaleEtablissementInnerResponse {
+                    unite_legale: Labelled::new(unite_legale),
+                    etablissement_siege: etablissement_siege.map(Labelled::new),
+                },
+            )
+        })
+        .collect();
+
+    let mut response = UnitesLegalesBatchResponse {
+        unites_legales: Vec::with_capacity(found.len()),
+        statuses: Vec::with_capacity(requested.len()),
+    };
+
+    for (siren, parsed) in requested {
+        let status = match parsed {
+            None => BatchLookupStatus::Invalid,
+            Some(parsed) => match found.remove(&parsed) {
+                Some(unite_legale) => {
+                    response.unites_legales.push(unite_legale);
+                    BatchLookupStatus::Found
+                }
+                None => BatchLookupStatus::NotFound,
+            },
+        };
+
+        response.statuses.push(BatchItemStatus {
+            identifier: siren,
+            status,
+        });
+    }
+
+    Ok(Json(response))
 }
 
 /// Search legal units
@@ -77,14 +495,26 @@ async fn search_unites_legales(
     let span = span!(Level::TRACE, "GET /unites_legales (search)");
     let _enter = span.enter();
 
-    // Validate sort constraints
-    match params.sort {
-        Some(UniteLegaleSortField::Relevance) if params.q.is_none() => {
+    validate_search_params(&params)?;
+
+    // Validate cursor constraints
+    if params.cursor.is_some() {
+        if params.offset.is_some() {
             return Err(Error::InvalidSearchParams {
-                message: "sort=relevance requires a q parameter".to_string(),
+                message: "cursor and offset cannot be used together".to_string(),
+            });
+        }
+
+        let relevance_sort = match params.sort {
+            Some(sort) => sort == UniteLegaleSortField::Relevance,
+            None => params.q.is_some(),
+        };
+
+        if relevance_sort {
+            return Err(Error::InvalidSearchParams {
+                message: "cursor cannot be used with sort=relevance".to_string(),
             });
         }
-        _ => {}
     }
 
     let connectors = context.builders.create();
@@ -110,30 +540,87 @@ async fn search_unites_legales(
         unites_legales: output
             .results
             .into_iter()
-            .map(|r| UniteLegaleSearchResultResponse {
-                siren: r.siren,
-                etat_administratif: r.etat_administratif,
-                date_creation: r.date_creation,
-                denomination: r.denomination,
-                denomination_usuelle_1: r.denomination_usuelle_1,
-                denomination_usuelle_2: r.denomination_usuelle_2,
-                denomination_usuelle_3: r.denomination_usuelle_3,
-                activite_principale: r.activite_principale,
-                categorie_juridique: r.categorie_juridique,
-                categorie_entreprise: r.categorie_entreprise,
-                score: r.score,
-            })
+            .map(UniteLegaleSearchResultResponse::from)
             .collect(),
         total,
         limit: output.limit,
         offset: output.offset,
         sort: output.sort,
         direction: output.direction,
+        next_cursor: output.next_cursor,
+        facets: output.facets,
     }))
 }
 
+/// Export all legal units matching a search
+#[utoipa::path(
+    get,
+    path = "/export",
+    params(UniteLegaleSearchParams),
+    responses(
+        (status = 200, description = "Every matching legal unit, streamed as CSV if `Accept: text/csv` is sent, NDJSON otherwise"),
+        (status = 400, description = "Invalid search parameters"),
+        (status = 401, description = "Missing or invalid API key"),
+        (status = 403, description = "API key not granted the export scope")
+    ),
+    tag = super::common::PUBLIC_TAG
+)]
+async fn export_unites_legales(
+    State(context): State<Arc<Context>>,
+    Extension(client): Extension<Client>,
+    headers: HeaderMap,
+    Query(params): Query<UniteLegaleSearchParams>,
+) -> Result<Response, Error> {
+    let span = span!(Level::TRACE, "GET /unites_legales/export");
+    let _enter = span.enter();
+
+    client.require(ApiScope::Export)?;
+    validate_search_params(&params)?;
+
+    let connectors = context.builders.create();
+    let mut connection = connectors
+        .local
+        .pool
+        .get()
+        .map_err(|e| Error::LocalConnectionFailed { source: e })?;
+
+    Ok(stream_export(
+        ExportFormat::from_headers(&headers),
+        move |on_batch| {
+            models::unite_legale::export(&mut connection, &params, |results| {
+                on_batch(
+                    results
+                        .into_iter()
+                        .map(UniteLegaleSearchResultResponse::from)
+                        .collect(),
+                )
+            })
+            .map_err(|e| e.into())
+        },
+    ))
+}
+
+fn validate_search_params(params: &UniteLegaleSearchParams) -> Result<(), Error> {
+    // Validate sort constraints
+    match params.sort {
+        Some(UniteLegaleSortField::Relevance) if params.q.is_none() => {
+            return Err(Error::InvalidSearchParams {
+                message: "sort=relevance requires a q parameter".to_string(),
+            });
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
 pub fn router() -> OpenApiRouter<Arc<Context>> {
     OpenApiRouter::new()
         .routes(routes!(get_unite_legale_by_siren))
+        .routes(routes!(get_unite_legale_by_numero_tva))
+        .routes(routes!(get_unite_legale_etablissements))
+        .routes(routes!(get_unite_legale_periodes))
         .routes(routes!(search_unites_legales))
+        .routes(routes!(post_unites_legales_batch))
+        .routes(routes!(export_unites_legales))
 }
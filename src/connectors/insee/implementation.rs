@@ -6,8 +6,10 @@ use super::types::{
 };
 use crate::connectors::insee::types::lien_succession::InseeLienSuccessionResponse;
 use crate::models::etablissement::common::EtablissementInsertable;
+use crate::models::etablissement_periode::common::EtablissementPeriode;
 use crate::models::lien_succession::common::LienSuccession;
 use crate::models::unite_legale::common::UniteLegaleInsertable;
+use crate::models::unite_legale_periode::common::UniteLegalePeriode;
 use chrono::NaiveDateTime;
 
 const MAX_CALL: u8 = 20;
@@ -87,7 +89,14 @@ impl Connector {
         &mut self,
         start_timestamp: NaiveDateTime,
         cursor: String,
-    ) -> Result<(Option<String>, Vec<UniteLegaleInsertable>), InseeUpdate> {
+    ) -> Result<
+        (
+            Option<String>,
+            Vec<UniteLegaleInsertable>,
+            Vec<UniteLegalePeriode>,
+        ),
+        InseeUpdate,
+    > {
         self.wait_for_insee_limitation().await;
 
         let (next_cursor, response) = get_daily_data::<InseeUniteLegaleResponse>(
@@ -98,16 +107,15 @@ impl Connector {
         )
         .await?;
 
+        let unites_legales = response.map(|resp| resp.unites_legales).unwrap_or_default();
+
         Ok((
             next_cursor,
-            match response {
-                Some(resp) => resp
-                    .unites_legales
-                    .iter()
-                    .filter_map(|u| u.into())
-                    .collect(),
-                None => vec![],
-            },
+            unites_legales.iter().filter_map(|u| u.into()).collect(),
+            unites_legales
+                .iter()
+                .flat_map(Vec::<UniteLegalePeriode>::from)
+                .collect(),
         ))
     }
 
@@ -115,7 +123,14 @@ impl Connector {
         &mut self,
         start_timestamp: NaiveDateTime,
         cursor: String,
-    ) -> Result<(Option<String>, Vec<EtablissementInsertable>), InseeUpdate> {
+    ) -> Result<
+        (
+            Option<String>,
+            Vec<EtablissementInsertable>,
+            Vec<EtablissementPeriode>,
+        ),
+        InseeUpdate,
+    > {
         self.wait_for_insee_limitation().await;
 
         let (next_cursor, response) = get_daily_data::<InseeEtablissementResponse>(
@@ -126,16 +141,15 @@ impl Connector {
         )
         .await?;
 
+        let etablissements = response.map(|resp| resp.etablissements).unwrap_or_default();
+
         Ok((
             next_cursor,
-            match response {
-                Some(resp) => resp
-                    .etablissements
-                    .iter()
-                    .filter_map(|u| u.into())
-                    .collect(),
-                None => vec![],
-            },
+            etablissements.iter().filter_map(|u| u.into()).collect(),
+            etablissements
+                .iter()
+                .flat_map(Vec::<EtablissementPeriode>::from)
+                .collect(),
         ))
     }
 
@@ -1,5 +1,6 @@
 use super::{Header, InseeResponse};
 use crate::models::etablissement::common::EtablissementInsertable;
+use crate::models::etablissement_periode::common::EtablissementPeriode;
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::Deserialize;
 
@@ -32,6 +33,9 @@ pub struct InseeEtablissementInner {
     pub etablissement_siege: bool,
     pub nombre_periodes_etablissement: Option<i32>,
     pub adresse_etablissement: InseeAdresseEtablissement,
+    // Not historized, only the current code is given
+    #[serde(rename = "activitePrincipaleNAF25Etablissement")]
+    pub activite_principale_naf25_etablissement: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -86,6 +90,16 @@ pub struct InseePeriodeEtablissement {
     activite_principale_etablissement: Option<String>,
     nomenclature_activite_principale_etablissement: Option<String>,
     caractere_employeur_etablissement: Option<String>,
+    #[serde(default)]
+    changement_etat_administratif_etablissement: bool,
+    #[serde(default)]
+    changement_enseigne_etablissement: bool,
+    #[serde(default)]
+    changement_denomination_usuelle_etablissement: bool,
+    #[serde(default)]
+    changement_activite_principale_etablissement: bool,
+    #[serde(default)]
+    changement_caractere_employeur_etablissement: bool,
 }
 
 impl From<&InseeEtablissement> for Option<EtablissementInsertable> {
@@ -103,6 +117,36 @@ impl From<&InseeEtablissement> for Option<EtablissementInsertable> {
     }
 }
 
+impl From<&InseeEtablissement> for Vec<EtablissementPeriode> {
+    fn from(e: &InseeEtablissement) -> Self {
+        e.periodes_etablissement
+            .iter()
+            .map(|p| EtablissementPeriode {
+                siren: e.content.siren.clone(),
+                nic: e.content.nic.clone(),
+                siret: e.content.siret.clone(),
+                date_fin: p.date_fin,
+                date_debut: p.date_debut,
+                etat_administratif: p.etat_administratif_etablissement.clone(),
+                changement_etat_administratif: p.changement_etat_administratif_etablissement,
+                enseigne_1: p.enseigne1_etablissement.clone(),
+                enseigne_2: p.enseigne2_etablissement.clone(),
+                enseigne_3: p.enseigne3_etablissement.clone(),
+                changement_enseigne: p.changement_enseigne_etablissement,
+                denomination_usuelle: p.denomination_usuelle_etablissement.clone(),
+                changement_denomination_usuelle: p.changement_denomination_usuelle_etablissement,
+                activite_principale: p.activite_principale_etablissement.clone(),
+                nomenclature_activite_principale: p
+                    .nomenclature_activite_principale_etablissement
+                    .clone(),
+                changement_activite_principale: p.changement_activite_principale_etablissement,
+                caractere_employeur: p.caractere_employeur_etablissement.clone(),
+                changement_caractere_employeur: p.changement_caractere_employeur_etablissement,
+            })
+            .collect()
+    }
+}
+
 impl From<InseeEtablissementWithPeriode> for EtablissementInsertable {
     fn from(e: InseeEtablissementWithPeriode) -> Self {
         let adresse = e.content.adresse_etablissement;
@@ -166,6 +210,7 @@ impl From<InseeEtablissementWithPeriode> for EtablissementInsertable {
                 .periode
                 .nomenclature_activite_principale_etablissement,
             caractere_employeur: e.periode.caractere_employeur_etablissement,
+            activite_principale_naf25: e.content.activite_principale_naf25_etablissement,
         }
     }
 }
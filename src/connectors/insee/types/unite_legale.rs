@@ -1,5 +1,6 @@
 use super::{Header, InseeResponse};
 use crate::models::unite_legale::common::UniteLegaleInsertable;
+use crate::models::unite_legale_periode::common::UniteLegalePeriode;
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::Deserialize;
 
@@ -46,6 +47,10 @@ pub struct InseeUniteLegaleInner {
 
     #[serde(deserialize_with = "super::from_str_optional")]
     pub annee_categorie_entreprise: Option<i32>,
+
+    // Not historized, only the current code is given
+    #[serde(rename = "activitePrincipaleNAF25UniteLegale")]
+    pub activite_principale_naf25_unite_legale: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -84,6 +89,29 @@ pub struct PeriodeInseeUniteLegale {
     pub economie_sociale_solidaire_unite_legale: Option<String>,
     pub societe_mission_unite_legale: Option<String>,
     pub caractere_employeur_unite_legale: Option<String>,
+
+    #[serde(default)]
+    pub changement_etat_administratif_unite_legale: bool,
+    #[serde(default)]
+    pub changement_nom_unite_legale: bool,
+    #[serde(default)]
+    pub changement_nom_usage_unite_legale: bool,
+    #[serde(default)]
+    pub changement_denomination_unite_legale: bool,
+    #[serde(default)]
+    pub changement_denomination_usuelle_unite_legale: bool,
+    #[serde(default)]
+    pub changement_categorie_juridique_unite_legale: bool,
+    #[serde(default)]
+    pub changement_activite_principale_unite_legale: bool,
+    #[serde(default)]
+    pub changement_nic_siege_unite_legale: bool,
+    #[serde(default)]
+    pub changement_economie_sociale_solidaire_unite_legale: bool,
+    #[serde(default)]
+    pub changement_societe_mission_unite_legale: bool,
+    #[serde(default)]
+    pub changement_caractere_employeur_unite_legale: bool,
 }
 
 impl From<&InseeUniteLegale> for Option<UniteLegaleInsertable> {
@@ -101,6 +129,47 @@ impl From<&InseeUniteLegale> for Option<UniteLegaleInsertable> {
     }
 }
 
+impl From<&InseeUniteLegale> for Vec<UniteLegalePeriode> {
+    fn from(u: &InseeUniteLegale) -> Self {
+        u.periodes_unite_legale
+            .iter()
+            .map(|p| UniteLegalePeriode {
+                siren: u.content.siren.clone(),
+                date_fin: p.date_fin,
+                date_debut: p.date_debut,
+                etat_administratif: p.etat_administratif_unite_legale.clone(),
+                changement_etat_administratif: p.changement_etat_administratif_unite_legale,
+                nom: p.nom_unite_legale.clone(),
+                changement_nom: p.changement_nom_unite_legale,
+                nom_usage: p.nom_usage_unite_legale.clone(),
+                changement_nom_usage: p.changement_nom_usage_unite_legale,
+                denomination: p.denomination_unite_legale.clone(),
+                changement_denomination: p.changement_denomination_unite_legale,
+                denomination_usuelle_1: p.denomination_usuelle1_unite_legale.clone(),
+                denomination_usuelle_2: p.denomination_usuelle2_unite_legale.clone(),
+                denomination_usuelle_3: p.denomination_usuelle3_unite_legale.clone(),
+                changement_denomination_usuelle: p.changement_denomination_usuelle_unite_legale,
+                categorie_juridique: p.categorie_juridique_unite_legale.clone(),
+                changement_categorie_juridique: p.changement_categorie_juridique_unite_legale,
+                activite_principale: p.activite_principale_unite_legale.clone(),
+                nomenclature_activite_principale: p
+                    .nomenclature_activite_principale_unite_legale
+                    .clone(),
+                changement_activite_principale: p.changement_activite_principale_unite_legale,
+                nic_siege: p.nic_siege_unite_legale.clone(),
+                changement_nic_siege: p.changement_nic_siege_unite_legale,
+                economie_sociale_solidaire: p.economie_sociale_solidaire_unite_legale.clone(),
+                changement_economie_sociale_solidaire: p
+                    .changement_economie_sociale_solidaire_unite_legale,
+                societe_mission: p.societe_mission_unite_legale.clone(),
+                changement_societe_mission: p.changement_societe_mission_unite_legale,
+                caractere_employeur: p.caractere_employeur_unite_legale.clone(),
+                changement_caractere_employeur: p.changement_caractere_employeur_unite_legale,
+            })
+            .collect()
+    }
+}
+
 impl From<InseeUniteLegaleWithPeriode> for UniteLegaleInsertable {
     fn from(u: InseeUniteLegaleWithPeriode) -> Self {
         UniteLegaleInsertable {
@@ -140,7 +209,7 @@ impl From<InseeUniteLegaleWithPeriode> for UniteLegaleInsertable {
             economie_sociale_solidaire: u.periode.economie_sociale_solidaire_unite_legale,
             societe_mission: u.periode.societe_mission_unite_legale,
             caractere_employeur: u.periode.caractere_employeur_unite_legale,
-            activite_principale_naf25: None,
+            activite_principale_naf25: u.content.activite_principale_naf25_unite_legale,
         }
     }
 }
@@ -0,0 +1,102 @@
+use super::super::schema::api_client;
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql};
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::{AsExpression, FromSqlRow, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use utoipa::ToSchema;
+
+#[derive(Queryable, Selectable, Serialize, Clone, Debug)]
+#[diesel(table_name = api_client)]
+pub struct ApiClient {
+    pub id: i32,
+    pub name: String,
+    /// Beginning of the key, displayed to identify it without revealing it
+    pub key_prefix: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApiClient {
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = api_client)]
+pub struct NewApiClient {
+    pub name: String,
+    pub key_prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Client created along with its key, which is not stored and only given once
+pub struct CreatedApiClient {
+    pub client: ApiClient,
+    pub key: String,
+}
+
+#[derive(
+    Debug, ToSchema, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, FromSqlRow, AsExpression,
+)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiScope {
+    /// Read access to the public endpoints
+    Public,
+    /// Bulk export of search results
+    Export,
+    /// Update and administration endpoints
+    Admin,
+    /// Unmasked data of partially diffused entities, reserved to administrators
+    FullDiffusion,
+}
+
+// SQL conversion
+impl ToSql<Text, Pg> for ApiScope {
+    fn to_sql(&self, out: &mut Output<Pg>) -> serialize::Result {
+        match *self {
+            ApiScope::Public => out.write_all(b"public")?,
+            ApiScope::Export => out.write_all(b"export")?,
+            ApiScope::Admin => out.write_all(b"admin")?,
+            ApiScope::FullDiffusion => out.write_all(b"full_diffusion")?,
+        }
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for ApiScope {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"public" => Ok(ApiScope::Public),
+            b"export" => Ok(ApiScope::Export),
+            b"admin" => Ok(ApiScope::Admin),
+            b"full_diffusion" => Ok(ApiScope::FullDiffusion),
+            _ => Err("Unrecognized enum variant".into()),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiScope::Public => write!(f, "public"),
+            ApiScope::Export => write!(f, "export"),
+            ApiScope::Admin => write!(f, "admin"),
+            ApiScope::FullDiffusion => write!(f, "full_diffusion"),
+        }
+    }
+}
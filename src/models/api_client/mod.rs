@@ -0,0 +1,134 @@
+pub mod common;
+pub mod error;
+
+use super::schema::api_client::dsl;
+use crate::connectors::Connectors;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Utc};
+use common::{ApiClient, ApiScope, CreatedApiClient, NewApiClient};
+use diesel::prelude::*;
+use error::Error;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+const KEY_PREFIX: &str = "sir_";
+const KEY_RANDOM_BYTES: usize = 32;
+// Length of the displayed prefix, marker included
+const DISPLAYED_PREFIX_LENGTH: usize = 12;
+const BOOTSTRAP_DISPLAYED_PREFIX_LENGTH: usize = 4;
+const BOOTSTRAP_CLIENT_NAME: &str = "api-key";
+
+/// Keys are random, a fast hash is enough to avoid storing them in clear
+fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; KEY_RANDOM_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+
+    format!("{KEY_PREFIX}{}", URL_SAFE_NO_PAD.encode(bytes))
+}
+
+pub fn create(
+    connectors: &Connectors,
+    name: String,
+    scopes: Vec<ApiScope>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<CreatedApiClient, Error> {
+    let mut connection = connectors.local.pool.get()?;
+    let key = generate_key();
+
+    let client = diesel::insert_into(dsl::api_client)
+        .values(&NewApiClient {
+            name,
+            key_prefix: key[..DISPLAYED_PREFIX_LENGTH].to_string(),
+            key_hash: hash_key(&key),
+            scopes,
+            expires_at,
+        })
+        .returning(ApiClient::as_returning())
+        .get_result(&mut connection)?;
+
+    Ok(CreatedApiClient { client, key })
+}
+
+/// Register `key`, chosen by the operator, as an admin client when no client
+/// exists yet, for deployments still configured with a single API key
+pub fn bootstrap(connectors: &Connectors, key: &str) -> Result<Option<ApiClient>, Error> {
+    let mut connection = connectors.local.pool.get()?;
+
+    connection
+        .build_transaction()
+        .read_write()
+        .run(|conn| {
+            let count = dsl::api_client.count().get_result::<i64>(conn)?;
+            if count > 0 {
+                return Ok(None);
+            }
+
+            // Only a few characters of a key that may be short are displayed
+            diesel::insert_into(dsl::api_client)
+                .values(&NewApiClient {
+                    name: BOOTSTRAP_CLIENT_NAME.to_string(),
+                    key_prefix: key
+                        .chars()
+                        .take(BOOTSTRAP_DISPLAYED_PREFIX_LENGTH)
+                        .collect(),
+                    key_hash: hash_key(key),
+                    scopes: vec![ApiScope::Public, ApiScope::Export, ApiScope::Admin],
+                    expires_at: None,
+                })
+                .returning(ApiClient::as_returning())
+                .get_result(conn)
+                .map(Some)
+        })
+        .map_err(|error: diesel::result::Error| error.into())
+}
+
+pub fn list(connectors: &Connectors) -> Result<Vec<ApiClient>, Error> {
+    let mut connection = connectors.local.pool.get()?;
+
+    dsl::api_client
+        .select(ApiClient::as_select())
+        .order(dsl::id.asc())
+        .load(&mut connection)
+        .map_err(|error| error.into())
+}
+
+/// Revoke a client, revoking an already revoked client keeps its revocation date
+pub fn revoke(connectors: &Connectors, id: i32) -> Result<ApiClient, Error> {
+    let mut connection = connectors.local.pool.get()?;
+
+    let revoked =
+        diesel::update(dsl::api_client.filter(dsl::id.eq(id).and(dsl::revoked_at.is_null())))
+            .set(dsl::revoked_at.eq(Utc::now()))
+            .returning(ApiClient::as_returning())
+            .get_result(&mut connection)
+            .optional()?;
+
+    match revoked {
+        Some(client) => Ok(client),
+        None => dsl::api_client
+            .select(ApiClient::as_select())
+            .filter(dsl::id.eq(id))
+            .first(&mut connection)
+            .map_err(|error| error.into()),
+    }
+}
+
+/// Find the active client owning `key`, if any
+pub fn authenticate(connectors: &Connectors, key: &str) -> Result<Option<ApiClient>, Error> {
+    let mut connection = connectors.local.pool.get()?;
+
+    let client = dsl::api_client
+        .select(ApiClient::as_select())
+        .filter(dsl::key_hash.eq(hash_key(key)))
+        .first(&mut connection)
+        .optional()?;
+
+    Ok(client.filter(|client| client.is_active(Utc::now())))
+}
@@ -0,0 +1,359 @@
+use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv_file};
+use super::schema::cog_commune::dsl;
+use crate::connectors::{Connectors, local::Connection};
+use crate::update::utils::remote_file::RemoteFile;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::pg::{CopyFormat, CopyHeader};
+use diesel::prelude::*;
+use diesel::sql_query;
+
+/// Tables loaded from the archive, swapped together
+const COG_TABLES: [&str; 6] = [
+    "cog_commune",
+    "cog_arrondissement",
+    "cog_departement",
+    "cog_region",
+    "cog_mouvement_commune",
+    "cog_commune_courante",
+];
+
+/// Match the department filter `$param_index` on `alias`, communes merged
+/// since the last update of the establishment being resolved to their
+/// current commune
+pub fn departement_condition(alias: &str, param_index: u32) -> String {
+    format!(
+        "{alias}.code_commune IN \
+        (SELECT code_commune FROM cog_commune_courante WHERE code_departement = ${param_index})"
+    )
+}
+
+/// Match the region filter `$param_index` on `alias`, as the department one
+pub fn region_condition(alias: &str, param_index: u32) -> String {
+    format!(
+        "{alias}.code_commune IN \
+        (SELECT code_commune FROM cog_commune_courante WHERE code_region = ${param_index})"
+    )
+}
+
+/// Fill `cog_commune_courante_staging` from the other staging tables: current
+/// communes first, then municipal arrondissements, delegated and associated
+/// communes through their parent, then former communes through the latest
+/// movement of each code until a current commune is reached
+fn resolve_communes_courantes(connection: &mut Connection) -> Result<(), diesel::result::Error> {
+    sql_query(
+        r#"
+        INSERT INTO cog_commune_courante_staging
+            (code_commune, code_commune_courante, code_departement, code_region)
+        SELECT code_commune, code_commune, code_departement, code_region
+        FROM cog_commune_staging
+        WHERE type_commune = 'COM'
+        AND code_departement IS NOT NULL
+        AND code_region IS NOT NULL
+        "#,
+    )
+    .execute(connection)?;
+
+    sql_query(
+        r#"
+        INSERT INTO cog_commune_courante_staging
+            (code_commune, code_commune_courante, code_departement, code_region)
+        SELECT
+            commune.code_commune,
+            CASE WHEN commune.type_commune = 'ARM' THEN commune.code_commune
+                ELSE parent.code_commune_courante END,
+            parent.code_departement,
+            parent.code_region
+        FROM cog_commune_staging commune
+        JOIN cog_commune_courante_staging parent
+            ON parent.code_commune = commune.code_commune_parent
+        WHERE commune.type_commune <> 'COM'
+        ON CONFLICT (code_commune) DO NOTHING
+        "#,
+    )
+    .execute(connection)?;
+
+    sql_query(
+        r#"
+        INSERT INTO cog_commune_courante_staging
+            (code_commune, code_commune_courante, code_departement, code_region)
+        WITH RECURSIVE successeur AS (
+            SELECT DISTINCT ON (code_commune_avant) code_commune_avant, code_commune_apres
+            FROM cog_mouvement_commune_staging
+            WHERE type_commune_avant = 'COM'
+            AND type_commune_apres = 'COM'
+            AND code_commune_avant <> code_commune_apres
+            ORDER BY code_commune_avant, date_effet DESC
+        ), chaine AS (
+            SELECT code_commune_avant AS code_commune, code_commune_apres, 1 AS profondeur
+            FROM successeur
+            UNION ALL
+            SELECT chaine.code_commune, successeur.code_commune_apres, chaine.profondeur + 1
+            FROM chaine
+            JOIN successeur ON successeur.code_commune_avant = chaine.code_commune_apres
+            WHERE chaine.profondeur < 32
+        )
+        SELECT DISTINCT ON (chaine.code_commune)
+            chaine.code_commune,
+            courante.code_commune_courante,
+            courante.code_departement,
+            courante.code_region
+        FROM chaine
+        JOIN cog_commune_courante_staging courante
+            ON courante.code_commune = chaine.code_commune_apres
+        ORDER BY chaine.code_commune, chaine.profondeur
+        ON CONFLICT (code_commune) DO NOTHING
+        "#,
+    )
+    .execute(connection)?;
+
+    Ok(())
+}
+
+pub struct CogModel {}
+
+#[async_trait]
+impl UpdatableModel for CogModel {
+    fn count(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        dsl::cog_commune
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn count_staging(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        use super::schema::cog_commune_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+        dsl::cog_commune_staging
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    // The archive holds one CSV file for each table
+    fn insert_remote_file_in_staging(
+        &self,
+        connectors: &Connectors,
+        remote_file: RemoteFile,
+    ) -> Result<bool, UpdatableError> {
+        use super::schema::{
+            cog_arrondissement_staging, cog_commune_staging, cog_departement_staging,
+            cog_mouvement_commune_staging, cog_region_staging,
+        };
+
+        let mut connection = connectors.local.pool.get()?;
+
+        for table in COG_TABLES {
+            sql_query(format!("TRUNCATE {table}_staging")).execute(&mut connection)?;
+        }
+
+        let count = {
+            use cog_commune_staging::dsl;
+
+            diesel::copy_from(dsl::cog_commune_staging)
+                .from_raw_data(
+                    (
+                        dsl::type_commune,
+                        dsl::code_commune,
+                        dsl::code_region,
+                        dsl::code_departement,
+                        dsl::code_collectivite,
+                        dsl::code_arrondissement,
+                        dsl::tncc,
+                        dsl::ncc,
+                        dsl::nccenr,
+                        dsl::libelle,
+                        dsl::code_canton,
+                        dsl::code_commune_parent,
+                    ),
+                    |write| {
+                        copy_remote_zipped_csv_file(remote_file.to_reader(), "v_commune_2", write)
+                    },
+                )
+                .with_delimiter(',')
+                .with_format(CopyFormat::Csv)
+                .with_header(CopyHeader::Set(true))
+                .execute(&mut connection)?
+        };
+
+        {
+            use cog_arrondissement_staging::dsl;
+
+            diesel::copy_from(dsl::cog_arrondissement_staging)
+                .from_raw_data(
+                    (
+                        dsl::code_arrondissement,
+                        dsl::code_departement,
+                        dsl::code_region,
+                        dsl::cheflieu,
+                        dsl::tncc,
+                        dsl::ncc,
+                        dsl::nccenr,
+                        dsl::libelle,
+                    ),
+                    |write| {
+                        copy_remote_zipped_csv_file(
+                            remote_file.to_reader(),
+                            "v_arrondissement_",
+                            write,
+                        )
+                    },
+                )
+                .with_delimiter(',')
+                .with_format(CopyFormat::Csv)
+                .with_header(CopyHeader::Set(true))
+                .execute(&mut connection)?;
+        }
+
+        {
+            use cog_departement_staging::dsl;
+
+            diesel::copy_from(dsl::cog_departement_staging)
+                .from_raw_data(
+                    (
+                        dsl::code_departement,
+                        dsl::code_region,
+                        dsl::cheflieu,
+                        dsl::tncc,
+                        dsl::ncc,
+                        dsl::nccenr,
+                        dsl::libelle,
+                    ),
+                    |write| {
+                        copy_remote_zipped_csv_file(
+                            remote_file.to_reader(),
+                            "v_departement_",
+                            write,
+                        )
+                    },
+                )
+                .with_delimiter(',')
+                .with_format(CopyFormat::Csv)
+                .with_header(CopyHeader::Set(true))
+                .execute(&mut connection)?;
+        }
+
+        {
+            use cog_region_staging::dsl;
+
+            diesel::copy_from(dsl::cog_region_staging)
+                .from_raw_data(
+                    (
+                        dsl::code_region,
+                        dsl::cheflieu,
+                        dsl::tncc,
+                        dsl::ncc,
+                        dsl::nccenr,
+                        dsl::libelle,
+                    ),
+                    |write| {
+                        copy_remote_zipped_csv_file(remote_file.to_reader(), "v_region_", write)
+                    },
+                )
+                .with_delimiter(',')
+                .with_format(CopyFormat::Csv)
+                .with_header(CopyHeader::Set(true))
+                .execute(&mut connection)?;
+        }
+
+        {
+            use cog_mouvement_commune_staging::dsl;
+
+            diesel::copy_from(dsl::cog_mouvement_commune_staging)
+                .from_raw_data(
+                    (
+                        dsl::type_evenement,
+                        dsl::date_effet,
+                        dsl::type_commune_avant,
+                        dsl::code_commune_avant,
+                        dsl::tncc_avant,
+                        dsl::ncc_avant,
+                        dsl::nccenr_avant,
+                        dsl::libelle_avant,
+                        dsl::type_commune_apres,
+                        dsl::code_commune_apres,
+                        dsl::tncc_apres,
+                        dsl::ncc_apres,
+                        dsl::nccenr_apres,
+                        dsl::libelle_apres,
+                    ),
+                    |write| {
+                        copy_remote_zipped_csv_file(
+                            remote_file.to_reader(),
+                            "v_mvt_commune_",
+                            write,
+                        )
+                    },
+                )
+                .with_delimiter(',')
+                .with_format(CopyFormat::Csv)
+                .with_header(CopyHeader::Set(true))
+                .execute(&mut connection)?;
+        }
+
+        resolve_communes_courantes(&mut connection)?;
+
+        Ok(count > 0)
+    }
+
+    fn swap(&self, connectors: &Connectors) -> Result<(), UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        connection.build_transaction().read_write().run(|conn| {
+            for table in COG_TABLES {
+                sql_query(format!("ALTER TABLE {table} RENAME TO {table}_temp")).execute(conn)?;
+                sql_query(format!("ALTER TABLE {table}_staging RENAME TO {table}"))
+                    .execute(conn)?;
+                sql_query(format!(
+                    "ALTER TABLE {table}_temp RENAME TO {table}_staging"
+                ))
+                .execute(conn)?;
+                sql_query(format!("TRUNCATE {table}_staging")).execute(conn)?;
+            }
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET last_imported_timestamp = staging_imported_timestamp
+                WHERE group_type = 'cog'
+                "#,
+            )
+            .execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET staging_imported_timestamp = NULL
+                WHERE group_type = 'cog'
+                "#,
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    // The geographic code is not published by the INSEE API
+    async fn get_total_count(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+    ) -> Result<u32, UpdatableError> {
+        Ok(0)
+    }
+
+    fn get_last_insee_synced_timestamp(
+        &self,
+        _connectors: &Connectors,
+    ) -> Result<Option<NaiveDateTime>, UpdatableError> {
+        Ok(None)
+    }
+
+    async fn update_daily_data(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+        _cursor: String,
+    ) -> Result<(Option<String>, usize), UpdatableError> {
+        Ok((None, 0))
+    }
+}
@@ -65,6 +65,49 @@ pub fn copy_remote_zipped_csv(
     diesel::QueryResult::Ok(())
 }
 
+/// Copy the CSV file of an archive holding several of them, the file being
+/// the first whose name starts with `prefix`
+pub fn copy_remote_zipped_csv_file(
+    zip_file: impl Read + Seek,
+    prefix: &str,
+    write: &mut dyn Write,
+) -> Result<(), diesel::result::Error> {
+    let mut archive = zip::ZipArchive::new(zip_file).map_err(|zip_error| {
+        diesel::result::Error::DeserializationError(Box::new(Error::ZipDecode { zip_error }))
+    })?;
+
+    let name = archive
+        .file_names()
+        .filter(|name| {
+            name.rsplit('/')
+                .next()
+                .is_some_and(|file_name| file_name.starts_with(prefix))
+        })
+        .min()
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            diesel::result::Error::DeserializationError(Box::new(Error::ZipMissingFile {
+                prefix: prefix.to_string(),
+            }))
+        })?;
+
+    let mut zipped_csv_file = archive.by_name(&name).map_err(|zip_error| {
+        diesel::result::Error::DeserializationError(Box::new(Error::ZipAccessFile { zip_error }))
+    })?;
+
+    debug!(
+        "Unzipping {} and inserting extracted to database ({} bytes)",
+        name,
+        zipped_csv_file.size()
+    );
+
+    std::io::copy(&mut zipped_csv_file, write).map_err(|io_error| {
+        diesel::result::Error::DeserializationError(Box::new(Error::FileCSVRead { io_error }))
+    })?;
+
+    diesel::QueryResult::Ok(())
+}
+
 custom_error! { pub Error
     LocalConnectionFailed{source: r2d2::Error} = "Unable to connect to local database ({source}).",
     Database{source: diesel::result::Error} = "Unable to run some operations on updatable model ({source}).",
@@ -73,6 +116,7 @@ custom_error! { pub Error
     ZipOpen {io_error: std::io::Error} = "Unable to open data zip file ({io_error})",
     ZipDecode {zip_error: zip::result::ZipError} = "Unable to decode zip file ({zip_error})",
     ZipFormat = "Archive has more than one file inside it, you should review it before running it again",
+    ZipMissingFile {prefix: String} = "Unable to find a file starting with {prefix} in archive",
     ZipAccessFile {zip_error: zip::result::ZipError} = "Unable to open file in archive ({zip_error})",
     FileCSVRead {io_error: std::io::Error} = "Unable to read CSV file from archive ({io_error})",
 }
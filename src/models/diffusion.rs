@@ -0,0 +1,414 @@
+use super::etablissement::common::{Etablissement, EtablissementSearchResultResponse};
+use super::unite_legale::common::UniteLegale;
+use super::unite_legale_periode::common::UniteLegalePeriode;
+
+/// `statut_diffusion` of natural persons who opted out of the public diffusion
+pub const PARTIAL_DIFFUSION: &str = "P";
+/// Value replacing masked data, as done by INSEE
+pub const NON_DIFFUSIBLE: &str = "[ND]";
+
+/// Entity subject to the INSEE diffusion rules
+pub trait Diffusible {
+    fn statut_diffusion(&self) -> &str;
+
+    /// Mask the data which cannot be diffused when the diffusion is partial
+    fn mask(&mut self);
+}
+
+/// Whether partially diffused entities are masked before being returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffusionPolicy {
+    Public,
+    Raw,
+}
+
+impl DiffusionPolicy {
+    pub fn apply<D: Diffusible>(self, entity: &mut D) {
+        if self == DiffusionPolicy::Public && entity.statut_diffusion() == PARTIAL_DIFFUSION {
+            entity.mask();
+        }
+    }
+
+    pub fn apply_all<D: Diffusible>(self, entities: &mut [D]) {
+        for entity in entities {
+            self.apply(entity);
+        }
+    }
+
+    /// Past names of a natural person, periods following the diffusion
+    /// status of their legal unit
+    pub fn apply_periodes(self, unite_legale: &UniteLegale, periodes: &mut [UniteLegalePeriode]) {
+        if self == DiffusionPolicy::Public && unite_legale.statut_diffusion() == PARTIAL_DIFFUSION {
+            for periode in periodes {
+                mask_text(&mut periode.nom);
+                mask_text(&mut periode.nom_usage);
+            }
+        }
+    }
+}
+
+fn mask_text(value: &mut Option<String>) {
+    if value.is_some() {
+        *value = Some(NON_DIFFUSIBLE.to_string());
+    }
+}
+
+/// Names and first names of the natural person
+impl Diffusible for UniteLegale {
+    fn statut_diffusion(&self) -> &str {
+        &self.statut_diffusion
+    }
+
+    fn mask(&mut self) {
+        for value in [
+            &mut self.sexe,
+            &mut self.prenom_1,
+            &mut self.prenom_2,
+            &mut self.prenom_3,
+            &mut self.prenom_4,
+            &mut self.prenom_usuel,
+            &mut self.pseudonyme,
+            &mut self.nom,
+            &mut self.nom_usage,
+        ] {
+            mask_text(value);
+        }
+    }
+}
+
+/// Address below the commune level and coordinates
+impl Diffusible for Etablissement {
+    fn statut_diffusion(&self) -> &str {
+        &self.statut_diffusion
+    }
+
+    fn mask(&mut self) {
+        for value in [
+            &mut self.complement_adresse,
+            &mut self.numero_voie,
+            &mut self.indice_repetition,
+            &mut self.dernier_numero_voie,
+            &mut self.indice_repetition_dernier_numero_voie,
+            &mut self.type_voie,
+            &mut self.libelle_voie,
+            &mut self.distribution_speciale,
+            &mut self.identifiant_adresse,
+            &mut self.coordonnee_lambert_x,
+            &mut self.coordonnee_lambert_y,
+            &mut self.complement_adresse2,
+            &mut self.numero_voie_2,
+            &mut self.indice_repetition_2,
+            &mut self.type_voie_2,
+            &mut self.libelle_voie_2,
+            &mut self.distribution_speciale_2,
+        ] {
+            mask_text(value);
+        }
+
+        self.position = None;
+    }
+}
+
+/// Coordinates, the distance to the searched point included
+impl Diffusible for EtablissementSearchResultResponse {
+    fn statut_diffusion(&self) -> &str {
+        &self.statut_diffusion
+    }
+
+    fn mask(&mut self) {
+        self.position = None;
+        self.meter_distance = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use postgis_diesel::types::Point;
+    use serde::Serialize;
+    use serde_json::{Value, json};
+
+    const ADDRESS_FIELDS: [&str; 17] = [
+        "complement_adresse",
+        "numero_voie",
+        "indice_repetition",
+        "dernier_numero_voie",
+        "indice_repetition_dernier_numero_voie",
+        "type_voie",
+        "libelle_voie",
+        "distribution_speciale",
+        "identifiant_adresse",
+        "coordonnee_lambert_x",
+        "coordonnee_lambert_y",
+        "complement_adresse2",
+        "numero_voie_2",
+        "indice_repetition_2",
+        "type_voie_2",
+        "libelle_voie_2",
+        "distribution_speciale_2",
+    ];
+
+    fn unite_legale(statut_diffusion: &str) -> UniteLegale {
+        UniteLegale {
+            siren: "siren".to_string(),
+            statut_diffusion: statut_diffusion.to_string(),
+            unite_purgee: Some("unite_purgee".to_string()),
+            date_creation: None,
+            sigle: Some("sigle".to_string()),
+            sexe: Some("sexe".to_string()),
+            prenom_1: Some("prenom_1".to_string()),
+            prenom_2: Some("prenom_2".to_string()),
+            prenom_3: Some("prenom_3".to_string()),
+            prenom_4: Some("prenom_4".to_string()),
+            prenom_usuel: Some("prenom_usuel".to_string()),
+            pseudonyme: Some("pseudonyme".to_string()),
+            identifiant_association: Some("identifiant_association".to_string()),
+            tranche_effectifs: Some("tranche_effectifs".to_string()),
+            annee_effectifs: None,
+            date_dernier_traitement: None,
+            nombre_periodes: None,
+            categorie_entreprise: Some("categorie_entreprise".to_string()),
+            annee_categorie_entreprise: None,
+            date_debut: None,
+            etat_administratif: "etat_administratif".to_string(),
+            nom: Some("nom".to_string()),
+            nom_usage: Some("nom_usage".to_string()),
+            denomination: Some("denomination".to_string()),
+            denomination_usuelle_1: Some("denomination_usuelle_1".to_string()),
+            denomination_usuelle_2: Some("denomination_usuelle_2".to_string()),
+            denomination_usuelle_3: Some("denomination_usuelle_3".to_string()),
+            categorie_juridique: Some("categorie_juridique".to_string()),
+            activite_principale: Some("activite_principale".to_string()),
+            nomenclature_activite_principale: Some("nomenclature_activite_principale".to_string()),
+            nic_siege: Some("nic_siege".to_string()),
+            economie_sociale_solidaire: Some("economie_sociale_solidaire".to_string()),
+            societe_mission: Some("societe_mission".to_string()),
+            caractere_employeur: Some("caractere_employeur".to_string()),
+            activite_principale_naf25: Some("activite_principale_naf25".to_string()),
+            numero_tva_intracommunautaire: Some("numero_tva_intracommunautaire".to_string()),
+        }
+    }
+
+    fn etablissement(statut_diffusion: &str) -> Etablissement {
+        Etablissement {
+            siren: "siren".to_string(),
+            nic: "nic".to_string(),
+            siret: "siret".to_string(),
+            statut_diffusion: statut_diffusion.to_string(),
+            date_creation: None,
+            tranche_effectifs: Some("tranche_effectifs".to_string()),
+            annee_effectifs: None,
+            activite_principale_registre_metiers: Some(
+                "activite_principale_registre_metiers".to_string(),
+            ),
+            date_dernier_traitement: None,
+            etablissement_siege: false,
+            nombre_periodes: None,
+            complement_adresse: Some("complement_adresse".to_string()),
+            numero_voie: Some("numero_voie".to_string()),
+            indice_repetition: Some("indice_repetition".to_string()),
+            dernier_numero_voie: Some("dernier_numero_voie".to_string()),
+            indice_repetition_dernier_numero_voie: Some(
+                "indice_repetition_dernier_numero_voie".to_string(),
+            ),
+            type_voie: Some("type_voie".to_string()),
+            libelle_voie: Some("libelle_voie".to_string()),
+            code_postal: Some("code_postal".to_string()),
+            libelle_commune: Some("libelle_commune".to_string()),
+            libelle_commune_etranger: Some("libelle_commune_etranger".to_string()),
+            distribution_speciale: Some("distribution_speciale".to_string()),
+            code_commune: Some("code_commune".to_string()),
+            code_cedex: Some("code_cedex".to_string()),
+            libelle_cedex: Some("libelle_cedex".to_string()),
+            code_pays_etranger: Some("code_pays_etranger".to_string()),
+            libelle_pays_etranger: Some("libelle_pays_etranger".to_string()),
+            identifiant_adresse: Some("identifiant_adresse".to_string()),
+            coordonnee_lambert_x: Some("coordonnee_lambert_x".to_string()),
+            coordonnee_lambert_y: Some("coordonnee_lambert_y".to_string()),
+            complement_adresse2: Some("complement_adresse2".to_string()),
+            numero_voie_2: Some("numero_voie_2".to_string()),
+            indice_repetition_2: Some("indice_repetition_2".to_string()),
+            type_voie_2: Some("type_voie_2".to_string()),
+            libelle_voie_2: Some("libelle_voie_2".to_string()),
+            code_postal_2: Some("code_postal_2".to_string()),
+            libelle_commune_2: Some("libelle_commune_2".to_string()),
+            libelle_commune_etranger_2: Some("libelle_commune_etranger_2".to_string()),
+            distribution_speciale_2: Some("distribution_speciale_2".to_string()),
+            code_commune_2: Some("code_commune_2".to_string()),
+            code_cedex_2: Some("code_cedex_2".to_string()),
+            libelle_cedex_2: Some("libelle_cedex_2".to_string()),
+            code_pays_etranger_2: Some("code_pays_etranger_2".to_string()),
+            libelle_pays_etranger_2: Some("libelle_pays_etranger_2".to_string()),
+            date_debut: None,
+            etat_administratif: "etat_administratif".to_string(),
+            enseigne_1: Some("enseigne_1".to_string()),
+            enseigne_2: Some("enseigne_2".to_string()),
+            enseigne_3: Some("enseigne_3".to_string()),
+            denomination_usuelle: Some("denomination_usuelle".to_string()),
+            activite_principale: Some("activite_principale".to_string()),
+            nomenclature_activite_principale: Some("nomenclature_activite_principale".to_string()),
+            caractere_employeur: Some("caractere_employeur".to_string()),
+            activite_principale_naf25: Some("activite_principale_naf25".to_string()),
+            position: Some(Point::new(2.35, 48.85, Some(4326))),
+            code_departement: Some("code_departement".to_string()),
+            code_region: Some("code_region".to_string()),
+        }
+    }
+
+    fn search_result(statut_diffusion: &str) -> EtablissementSearchResultResponse {
+        EtablissementSearchResultResponse {
+            siret: "siret".to_string(),
+            siren: "siren".to_string(),
+            statut_diffusion: statut_diffusion.to_string(),
+            etat_administratif: "etat_administratif".to_string(),
+            date_creation: None,
+            denomination_usuelle: Some("denomination_usuelle".to_string()),
+            enseigne_1: Some("enseigne_1".to_string()),
+            enseigne_2: Some("enseigne_2".to_string()),
+            enseigne_3: Some("enseigne_3".to_string()),
+            code_postal: Some("code_postal".to_string()),
+            libelle_commune: Some("libelle_commune".to_string()),
+            code_departement: Some("code_departement".to_string()),
+            code_region: Some("code_region".to_string()),
+            activite_principale: Some("activite_principale".to_string()),
+            etablissement_siege: false,
+            position: Some(Point::new(2.35, 48.85, Some(4326))),
+            meter_distance: Some(12.0),
+            score: None,
+        }
+    }
+
+    fn periode() -> UniteLegalePeriode {
+        UniteLegalePeriode {
+            siren: "siren".to_string(),
+            date_fin: None,
+            date_debut: None,
+            etat_administratif: "etat_administratif".to_string(),
+            changement_etat_administratif: false,
+            nom: Some("nom".to_string()),
+            changement_nom: false,
+            nom_usage: Some("nom_usage".to_string()),
+            changement_nom_usage: false,
+            denomination: Some("denomination".to_string()),
+            changement_denomination: false,
+            denomination_usuelle_1: Some("denomination_usuelle_1".to_string()),
+            denomination_usuelle_2: Some("denomination_usuelle_2".to_string()),
+            denomination_usuelle_3: Some("denomination_usuelle_3".to_string()),
+            changement_denomination_usuelle: false,
+            categorie_juridique: Some("categorie_juridique".to_string()),
+            changement_categorie_juridique: false,
+            activite_principale: Some("activite_principale".to_string()),
+            nomenclature_activite_principale: Some("nomenclature_activite_principale".to_string()),
+            changement_activite_principale: false,
+            nic_siege: Some("nic_siege".to_string()),
+            changement_nic_siege: false,
+            economie_sociale_solidaire: Some("economie_sociale_solidaire".to_string()),
+            changement_economie_sociale_solidaire: false,
+            societe_mission: Some("societe_mission".to_string()),
+            changement_societe_mission: false,
+            caractere_employeur: Some("caractere_employeur".to_string()),
+            changement_caractere_employeur: false,
+        }
+    }
+
+    /// Check that `policy` changes exactly the `masked` fields of `entity`
+    fn assert_masked<D: Diffusible + Serialize>(
+        policy: DiffusionPolicy,
+        mut entity: D,
+        masked: &[(&str, Value)],
+    ) {
+        let mut expected = serde_json::to_value(&entity).unwrap();
+        for (field, value) in masked {
+            assert!(expected.get(field).is_some(), "unknown field {field}");
+            expected[field] = value.clone();
+        }
+
+        policy.apply(&mut entity);
+
+        assert_eq!(serde_json::to_value(&entity).unwrap(), expected);
+    }
+
+    fn non_diffusible(fields: &[&'static str]) -> Vec<(&'static str, Value)> {
+        fields
+            .iter()
+            .map(|field| (*field, json!(NON_DIFFUSIBLE)))
+            .collect()
+    }
+
+    #[test]
+    fn masks_natural_person_of_partial_unite_legale() {
+        assert_masked(
+            DiffusionPolicy::Public,
+            unite_legale(PARTIAL_DIFFUSION),
+            &non_diffusible(&[
+                "sexe",
+                "prenom_1",
+                "prenom_2",
+                "prenom_3",
+                "prenom_4",
+                "prenom_usuel",
+                "pseudonyme",
+                "nom",
+                "nom_usage",
+            ]),
+        );
+    }
+
+    #[test]
+    fn masks_address_and_position_of_partial_etablissement() {
+        let mut masked = non_diffusible(&ADDRESS_FIELDS);
+        masked.push(("position", Value::Null));
+
+        assert_masked(
+            DiffusionPolicy::Public,
+            etablissement(PARTIAL_DIFFUSION),
+            &masked,
+        );
+    }
+
+    #[test]
+    fn masks_position_of_partial_search_result() {
+        let mut result = search_result(PARTIAL_DIFFUSION);
+        let mut expected = serde_json::to_value(&result).unwrap();
+        expected["position"] = Value::Null;
+        // The distance is left out of the response once unknown
+        expected.as_object_mut().unwrap().remove("meter_distance");
+
+        DiffusionPolicy::Public.apply(&mut result);
+
+        assert_eq!(serde_json::to_value(&result).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_entities_under_full_diffusion() {
+        assert_masked(DiffusionPolicy::Raw, unite_legale(PARTIAL_DIFFUSION), &[]);
+        assert_masked(DiffusionPolicy::Raw, etablissement(PARTIAL_DIFFUSION), &[]);
+        assert_masked(DiffusionPolicy::Raw, search_result(PARTIAL_DIFFUSION), &[]);
+    }
+
+    #[test]
+    fn keeps_diffusible_entities() {
+        assert_masked(DiffusionPolicy::Public, unite_legale("O"), &[]);
+        assert_masked(DiffusionPolicy::Public, etablissement("O"), &[]);
+        assert_masked(DiffusionPolicy::Public, search_result("O"), &[]);
+    }
+
+    #[test]
+    fn masks_names_of_periodes_following_their_unite_legale() {
+        let mut periodes = vec![periode()];
+        DiffusionPolicy::Public.apply_periodes(&unite_legale(PARTIAL_DIFFUSION), &mut periodes);
+        assert_eq!(periodes[0].nom.as_deref(), Some(NON_DIFFUSIBLE));
+        assert_eq!(periodes[0].nom_usage.as_deref(), Some(NON_DIFFUSIBLE));
+        assert_eq!(periodes[0].denomination.as_deref(), Some("denomination"));
+
+        for (policy, statut_diffusion) in [
+            (DiffusionPolicy::Raw, PARTIAL_DIFFUSION),
+            (DiffusionPolicy::Public, "O"),
+        ] {
+            let mut periodes = vec![periode()];
+            policy.apply_periodes(&unite_legale(statut_diffusion), &mut periodes);
+            assert_eq!(periodes[0].nom.as_deref(), Some("nom"));
+            assert_eq!(periodes[0].nom_usage.as_deref(), Some("nom_usage"));
+        }
+    }
+}
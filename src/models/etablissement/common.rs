@@ -1,10 +1,12 @@
-use super::super::schema::etablissement;
+use super::super::schema::{cog_commune_courante, etablissement};
+use super::super::search_facet::FacetBucket;
 use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use diesel::sql_types::{BigInt, Bool, Float4, Float8, Jsonb, Nullable, Text, VarChar};
 use postgis_diesel::sql_types::Geography;
 use postgis_diesel::types::Point;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::{IntoParams, ToSchema};
 
 #[derive(ToSchema, Serialize, Clone, Debug)]
@@ -69,6 +71,7 @@ pub struct EtablissementInsertable {
     pub activite_principale: Option<String>,
     pub nomenclature_activite_principale: Option<String>,
     pub caractere_employeur: Option<String>,
+    pub activite_principale_naf25: Option<String>,
 }
 
 #[derive(Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
@@ -130,9 +133,17 @@ pub struct Etablissement {
     pub activite_principale_naf25: Option<String>,
     #[schema(value_type = Option<EtablissementPoint>)]
     pub position: Option<Point>,
+    /// Department of the commune, merged communes being resolved to their current one
+    #[diesel(select_expression = cog_commune_courante::code_departement.nullable())]
+    #[diesel(select_expression_type = diesel::dsl::Nullable<cog_commune_courante::code_departement>)]
+    pub code_departement: Option<String>,
+    /// Region of the commune, merged communes being resolved to their current one
+    #[diesel(select_expression = cog_commune_courante::code_region.nullable())]
+    #[diesel(select_expression_type = diesel::dsl::Nullable<cog_commune_courante::code_region>)]
+    pub code_region: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum EtablissementSortField {
     DateCreation,
@@ -141,35 +152,146 @@ pub enum EtablissementSortField {
     Relevance,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SortDirection {
     Asc,
     Desc,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EtablissementFacet {
+    ActivitePrincipale,
+    EtatAdministratif,
+    CodePostal,
+    TrancheEffectifs,
+}
+
+impl EtablissementFacet {
+    /// Column counted by the facet, also indexed as a literal bm25 field
+    pub fn field(self) -> &'static str {
+        match self {
+            EtablissementFacet::ActivitePrincipale => "activite_principale",
+            EtablissementFacet::EtatAdministratif => "etat_administratif",
+            EtablissementFacet::CodePostal => "code_postal",
+            EtablissementFacet::TrancheEffectifs => "tranche_effectifs",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    Json,
+    Geojson,
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
 pub enum EtatAdministratif {
     A,
     F,
 }
 
-#[derive(Debug, Deserialize, IntoParams)]
+#[derive(Debug, Default, Deserialize, IntoParams)]
 pub struct EtablissementSearchParams {
     pub q: Option<String>,
     pub etat_administratif: Option<EtatAdministratif>,
     pub code_postal: Option<String>,
     pub siren: Option<String>,
     pub code_commune: Option<String>,
+    /// Department code, e.g. `33` or `2A`
+    pub code_departement: Option<String>,
+    /// Region code, e.g. `75`
+    pub code_region: Option<String>,
     pub activite_principale: Option<String>,
+    pub activite_principale_naf25: Option<String>,
     pub etablissement_siege: Option<bool>,
     pub lat: Option<f64>,
     pub lng: Option<f64>,
     pub radius: Option<f64>,
+    pub bbox: Option<String>,
+    /// GeoJSON polygon or multipolygon, only given in the body of POST searches
+    #[serde(skip)]
+    #[param(ignore)]
+    pub geometry: Option<String>,
     pub sort: Option<EtablissementSortField>,
     pub direction: Option<SortDirection>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
+    pub facets: Option<String>,
+    pub format: Option<ResponseFormat>,
+}
+
+/// Parse a `minLng,minLat,maxLng,maxLat` bounding box
+pub fn parse_bbox(bbox: &str) -> Option<[f64; 4]> {
+    let values = bbox
+        .split(',')
+        .map(|value| value.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<f64>>>()?;
+
+    match values[..] {
+        [min_lng, min_lat, max_lng, max_lat]
+            if (-180.0..=180.0).contains(&min_lng)
+                && (-180.0..=180.0).contains(&max_lng)
+                && (-90.0..=90.0).contains(&min_lat)
+                && (-90.0..=90.0).contains(&max_lat)
+                && min_lng < max_lng
+                && min_lat < max_lat =>
+        {
+            Some([min_lng, min_lat, max_lng, max_lat])
+        }
+        _ => None,
+    }
+}
+
+/// Check that `geometry` is a GeoJSON Polygon or MultiPolygon made of closed
+/// rings of at least 4 longitude, latitude (and optional altitude) positions
+pub fn is_valid_geometry(geometry: &serde_json::Value) -> bool {
+    let coordinates = &geometry["coordinates"];
+
+    match geometry["type"].as_str() {
+        Some("Polygon") => is_polygon(coordinates),
+        Some("MultiPolygon") => coordinates
+            .as_array()
+            .is_some_and(|polygons| !polygons.is_empty() && polygons.iter().all(is_polygon)),
+        _ => false,
+    }
+}
+
+fn is_polygon(polygon: &serde_json::Value) -> bool {
+    polygon
+        .as_array()
+        .is_some_and(|rings| !rings.is_empty() && rings.iter().all(is_ring))
+}
+
+fn is_ring(ring: &serde_json::Value) -> bool {
+    let Some(positions) = ring
+        .as_array()
+        .and_then(|ring| ring.iter().map(position).collect::<Option<Vec<_>>>())
+    else {
+        return false;
+    };
+
+    positions.len() >= 4 && positions.first() == positions.last()
+}
+
+fn position(position: &serde_json::Value) -> Option<Vec<f64>> {
+    let values = position
+        .as_array()?
+        .iter()
+        .map(|value| value.as_f64().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<f64>>>()?;
+
+    match values[..] {
+        [lng, lat] | [lng, lat, _]
+            if (-180.0..=180.0).contains(&lng) && (-90.0..=90.0).contains(&lat) =>
+        {
+            Some(values)
+        }
+        _ => None,
+    }
 }
 
 #[derive(Debug, QueryableByName, Serialize, ToSchema)]
@@ -179,6 +301,8 @@ pub struct EtablissementSearchResult {
     #[diesel(sql_type = VarChar)]
     pub siren: String,
     #[diesel(sql_type = VarChar)]
+    pub statut_diffusion: String,
+    #[diesel(sql_type = VarChar)]
     pub etat_administratif: String,
     #[diesel(sql_type = Nullable<diesel::sql_types::Date>)]
     pub date_creation: Option<NaiveDate>,
@@ -194,6 +318,10 @@ pub struct EtablissementSearchResult {
     pub code_postal: Option<String>,
     #[diesel(sql_type = Nullable<Text>)]
     pub libelle_commune: Option<String>,
+    #[diesel(sql_type = Nullable<VarChar>)]
+    pub code_departement: Option<String>,
+    #[diesel(sql_type = Nullable<VarChar>)]
+    pub code_region: Option<String>,
     #[diesel(sql_type = Nullable<Text>)]
     pub activite_principale: Option<String>,
     #[diesel(sql_type = Bool)]
@@ -206,6 +334,8 @@ pub struct EtablissementSearchResult {
     pub total: Option<i64>,
     #[diesel(sql_type = Nullable<Jsonb>)]
     pub total_json: Option<serde_json::Value>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub sort_value: Option<String>,
     #[schema(value_type = Option<EtablissementPoint>)]
     #[diesel(sql_type = Nullable<Geography>)]
     pub position: Option<Point>,
@@ -217,6 +347,8 @@ pub struct EtablissementSearchOutput {
     pub offset: i64,
     pub sort: EtablissementSortField,
     pub direction: SortDirection,
+    pub next_cursor: Option<String>,
+    pub facets: Option<BTreeMap<String, Vec<FacetBucket>>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -227,12 +359,16 @@ pub struct EtablissementSearchResponse {
     pub offset: i64,
     pub sort: EtablissementSortField,
     pub direction: SortDirection,
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<BTreeMap<String, Vec<FacetBucket>>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct EtablissementSearchResultResponse {
     pub siret: String,
     pub siren: String,
+    pub statut_diffusion: String,
     pub etat_administratif: String,
     pub date_creation: Option<NaiveDate>,
     pub denomination_usuelle: Option<String>,
@@ -241,6 +377,8 @@ pub struct EtablissementSearchResultResponse {
     pub enseigne_3: Option<String>,
     pub code_postal: Option<String>,
     pub libelle_commune: Option<String>,
+    pub code_departement: Option<String>,
+    pub code_region: Option<String>,
     pub activite_principale: Option<String>,
     pub etablissement_siege: bool,
     #[schema(value_type = Option<EtablissementPoint>)]
@@ -250,3 +388,89 @@ pub struct EtablissementSearchResultResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score: Option<f32>,
 }
+
+impl From<EtablissementSearchResult> for EtablissementSearchResultResponse {
+    fn from(r: EtablissementSearchResult) -> Self {
+        EtablissementSearchResultResponse {
+            siret: r.siret,
+            siren: r.siren,
+            statut_diffusion: r.statut_diffusion,
+            etat_administratif: r.etat_administratif,
+            date_creation: r.date_creation,
+            denomination_usuelle: r.denomination_usuelle,
+            enseigne_1: r.enseigne_1,
+            enseigne_2: r.enseigne_2,
+            enseigne_3: r.enseigne_3,
+            code_postal: r.code_postal,
+            libelle_commune: r.libelle_commune,
+            code_departement: r.code_departement,
+            code_region: r.code_region,
+            activite_principale: r.activite_principale,
+            etablissement_siege: r.etablissement_siege,
+            position: r.position,
+            meter_distance: r.meter_distance,
+            score: r.score,
+        }
+    }
+}
+
+impl From<Etablissement> for EtablissementSearchResultResponse {
+    fn from(e: Etablissement) -> Self {
+        EtablissementSearchResultResponse {
+            siret: e.siret,
+            siren: e.siren,
+            statut_diffusion: e.statut_diffusion,
+            etat_administratif: e.etat_administratif,
+            date_creation: e.date_creation,
+            denomination_usuelle: e.denomination_usuelle,
+            enseigne_1: e.enseigne_1,
+            enseigne_2: e.enseigne_2,
+            enseigne_3: e.enseigne_3,
+            code_postal: e.code_postal,
+            libelle_commune: e.libelle_commune,
+            code_departement: e.code_departement,
+            code_region: e.code_region,
+            activite_principale: e.activite_principale,
+            etablissement_siege: e.etablissement_siege,
+            position: e.position,
+            meter_distance: None,
+            score: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn valid_geometries() {
+        for geometry in [
+            json!({"type": "Polygon", "coordinates": [[[2.3, 48.8], [2.4, 48.8], [2.4, 48.9], [2.3, 48.8]]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3, 48.8, 35.0], [2.4, 48.8, 35.0], [2.4, 48.9, 35.0], [2.3, 48.8, 35.0]]]}),
+            json!({"type": "MultiPolygon", "coordinates": [[[[2.3, 48.8], [2.4, 48.8], [2.4, 48.9], [2.3, 48.8]]]]}),
+        ] {
+            assert!(is_valid_geometry(&geometry), "{geometry}");
+        }
+    }
+
+    #[test]
+    fn malformed_geometries() {
+        for geometry in [
+            json!({"type": "Point", "coordinates": [2.3, 48.8]}),
+            json!({"type": "Polygon"}),
+            json!({"type": "Polygon", "coordinates": []}),
+            json!({"type": "Polygon", "coordinates": [[1, 2]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3, 48.8], [2.4, 48.8], [2.3, 48.8]]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3, 48.8], [2.4, 48.8], [2.4, 48.9], [2.3, 48.9]]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3], [2.4, 48.8], [2.4, 48.9], [2.3]]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3, 48.8, 1.0, 2.0], [2.4, 48.8], [2.4, 48.9], [2.3, 48.8, 1.0, 2.0]]]}),
+            json!({"type": "Polygon", "coordinates": [[["2.3", 48.8], [2.4, 48.8], [2.4, 48.9], ["2.3", 48.8]]]}),
+            json!({"type": "Polygon", "coordinates": [[[2.3, 98.8], [2.4, 48.8], [2.4, 48.9], [2.3, 98.8]]]}),
+            json!({"type": "MultiPolygon", "coordinates": [[[2.3, 48.8], [2.4, 48.8], [2.4, 48.9], [2.3, 48.8]]]}),
+        ] {
+            assert!(!is_valid_geometry(&geometry), "{geometry}");
+        }
+    }
+}
@@ -3,6 +3,8 @@ use custom_error::custom_error;
 custom_error! { pub Error
     LocalConnectionFailed{source: r2d2::Error} = "Unable to connect to local database ({source}).",
     EtablissementNotFound = "Etablissement not found.",
+    InvalidCursor = "Invalid or mismatching search cursor.",
+    InvalidFacet{name: String} = "Unknown search facet {name}.",
     Database{diesel_error: diesel::result::Error} = "Unable to run some operations on etablissement ({diesel_error}).",
 }
 
@@ -1,26 +1,45 @@
 pub mod common;
 pub mod error;
 
+use super::cog::{departement_condition, region_condition};
 use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::diffusion::{DiffusionPolicy, PARTIAL_DIFFUSION};
+use super::naf_correspondance::{naf_rev2_condition, naf25_condition};
+use super::schema::cog_commune_courante;
 use super::schema::etablissement::dsl;
+use super::search_cursor::{SearchCursor, keyset_condition};
+use super::search_facet::{FacetBucket, facet_query, load_facet, parse_facets};
 use crate::connectors::{Connectors, local::Connection};
 use crate::update::utils::remote_file::RemoteFile;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use common::{
-    Etablissement, EtablissementSearchOutput, EtablissementSearchParams, EtablissementSearchResult,
-    EtablissementSortField, SortDirection,
+    Etablissement, EtablissementFacet, EtablissementSearchOutput, EtablissementSearchParams,
+    EtablissementSearchResult, EtablissementSortField, SortDirection, parse_bbox,
 };
 use diesel::pg::upsert::excluded;
-use diesel::pg::{CopyFormat, CopyHeader};
+use diesel::pg::{CopyFormat, CopyHeader, Pg};
 use diesel::prelude::*;
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
 use diesel::sql_query;
-use diesel::sql_types::{Bool, Float8, Text};
+use diesel::sql_types::{Bool, Bytea, Float8, Nullable, Text};
 use error::Error;
+use std::collections::BTreeMap;
+
+/// Establishments joined with the current commune of their code, which
+/// gives their department and region
+#[diesel::dsl::auto_type(no_type_alias)]
+fn etablissement_with_cog() -> _ {
+    dsl::etablissement.left_join(
+        cog_commune_courante::table.on(cog_commune_courante::code_commune
+            .nullable()
+            .eq(dsl::code_commune)),
+    )
+}
 
 pub fn get(connection: &mut Connection, siret: &str) -> Result<Etablissement, Error> {
-    dsl::etablissement
-        .find(siret)
+    etablissement_with_cog()
+        .filter(dsl::siret.eq(siret))
         .select(Etablissement::as_select())
         .first::<Etablissement>(connection)
         .map_err(|error| error.into())
@@ -30,38 +49,172 @@ pub fn get_with_siren(
     connection: &mut Connection,
     siren: &str,
 ) -> Result<Vec<Etablissement>, Error> {
-    dsl::etablissement
+    etablissement_with_cog()
         .filter(dsl::siren.eq(siren))
         .select(Etablissement::as_select())
         .load::<Etablissement>(connection)
         .map_err(|error| error.into())
 }
 
+/// Number of establishments of a SIREN for each etat_administratif
+pub fn count_with_siren(
+    connection: &mut Connection,
+    siren: &str,
+) -> Result<Vec<(String, i64)>, Error> {
+    dsl::etablissement
+        .filter(dsl::siren.eq(siren))
+        .group_by(dsl::etat_administratif)
+        .select((dsl::etat_administratif, diesel::dsl::count_star()))
+        .load::<(String, i64)>(connection)
+        .map_err(|error| error.into())
+}
+
 pub fn get_siege_with_siren(
     connection: &mut Connection,
     siren: &str,
 ) -> Result<Etablissement, Error> {
-    dsl::etablissement
+    etablissement_with_cog()
         .filter(dsl::siren.eq(siren).and(dsl::etablissement_siege.eq(true)))
         .select(Etablissement::as_select())
         .first::<Etablissement>(connection)
         .map_err(|error| error.into())
 }
 
-pub fn search(
+pub fn get_batch(
     connection: &mut Connection,
+    sirets: &[String],
+) -> Result<Vec<Etablissement>, Error> {
+    etablissement_with_cog()
+        .filter(dsl::siret.eq_any(sirets))
+        .select(Etablissement::as_select())
+        .load::<Etablissement>(connection)
+        .map_err(|error| error.into())
+}
+
+pub fn get_sieges_with_sirens(
+    connection: &mut Connection,
+    sirens: &[String],
+) -> Result<Vec<Etablissement>, Error> {
+    etablissement_with_cog()
+        .filter(
+            dsl::siren
+                .eq_any(sirens)
+                .and(dsl::etablissement_siege.eq(true)),
+        )
+        .select(Etablissement::as_select())
+        .load::<Etablissement>(connection)
+        .map_err(|error| error.into())
+}
+
+type EtablissementCursor = SearchCursor<EtablissementSortField, SortDirection>;
+
+const EXPORT_CURSOR: &str = "etablissement_export";
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+/// Below this zoom level, tiles contain counts on a grid instead of points
+pub const TILE_CLUSTER_MAX_ZOOM: u32 = 12;
+pub const TILE_MAX_ZOOM: u32 = 22;
+const TILE_GRID_CELLS: u32 = 64;
+const TILE_MAX_FEATURES: u32 = 20_000;
+const WEB_MERCATOR_EXTENT: f64 = 40_075_016.685_578_49;
+
+/// FROM and WHERE clauses shared by every query built from search parameters
+struct SearchFilter {
+    from_clause: String,
+    where_clause: String,
+}
+
+fn resolve_sort(params: &EtablissementSearchParams) -> (EtablissementSortField, SortDirection) {
+    let sort_field = params.sort.unwrap_or(if params.q.is_some() {
+        EtablissementSortField::Relevance
+    } else {
+        EtablissementSortField::DateCreation
+    });
+
+    let resolved_dir = params.direction.unwrap_or(match sort_field {
+        EtablissementSortField::Distance => SortDirection::Asc,
+        _ => SortDirection::Desc,
+    });
+
+    (sort_field, resolved_dir)
+}
+
+// Partially diffused ('P') establishments cannot be located, their distance
+// is unknown under the public policy
+fn distance_expression(policy: DiffusionPolicy) -> &'static str {
+    match policy {
+        DiffusionPolicy::Public => {
+            "(CASE WHEN e.statut_diffusion <> 'P' THEN e.position <-> ref_point.pt END)"
+        }
+        DiffusionPolicy::Raw => "(e.position <-> ref_point.pt)",
+    }
+}
+
+// Condition appended to every filter on the position of establishments
+fn located_condition(policy: DiffusionPolicy) -> String {
+    match policy {
+        DiffusionPolicy::Public => format!(" AND e.statut_diffusion <> '{PARTIAL_DIFFUSION}'"),
+        DiffusionPolicy::Raw => String::new(),
+    }
+}
+
+// Relevance scores cannot be used as a keyset
+fn sort_key(
+    sort_field: EtablissementSortField,
+    policy: DiffusionPolicy,
+) -> Option<(&'static str, &'static str)> {
+    match sort_field {
+        EtablissementSortField::DateCreation => Some(("e.date_creation", "date")),
+        EtablissementSortField::DateDebut => Some(("e.date_debut", "date")),
+        EtablissementSortField::Distance => Some((distance_expression(policy), "float8")),
+        EtablissementSortField::Relevance => None,
+    }
+}
+
+fn order_by(
+    sort_field: EtablissementSortField,
+    direction: SortDirection,
+    policy: DiffusionPolicy,
+) -> String {
+    match (sort_field, direction) {
+        (EtablissementSortField::Distance, SortDirection::Desc) => format!(
+            "{} DESC NULLS LAST, e.siret DESC",
+            distance_expression(policy)
+        ),
+        (EtablissementSortField::Distance, SortDirection::Asc) => {
+            format!(
+                "{} ASC NULLS LAST, e.siret ASC",
+                distance_expression(policy)
+            )
+        }
+        (EtablissementSortField::Relevance, SortDirection::Asc) => "score ASC".to_string(),
+        (EtablissementSortField::Relevance, SortDirection::Desc) => "score DESC".to_string(),
+        (EtablissementSortField::DateCreation, SortDirection::Asc) => {
+            "e.date_creation ASC NULLS LAST, e.siret ASC".to_string()
+        }
+        (EtablissementSortField::DateCreation, SortDirection::Desc) => {
+            "e.date_creation DESC NULLS LAST, e.siret DESC".to_string()
+        }
+        (EtablissementSortField::DateDebut, SortDirection::Asc) => {
+            "e.date_debut ASC NULLS LAST, e.siret ASC".to_string()
+        }
+        (EtablissementSortField::DateDebut, SortDirection::Desc) => {
+            "e.date_debut DESC NULLS LAST, e.siret DESC".to_string()
+        }
+    }
+}
+
+fn select_columns(
     params: &EtablissementSearchParams,
-) -> Result<EtablissementSearchOutput, Error> {
+    with_total: bool,
+    sort_key: Option<(&str, &str)>,
+) -> Vec<String> {
     let has_geo = params.lat.is_some() && params.lng.is_some() && params.radius.is_some();
-    let has_q = params.q.is_some();
-
-    let limit = params.limit.unwrap_or(20).clamp(1, 100);
-    let offset = params.offset.unwrap_or(0).clamp(0, 10_000);
 
-    // Build SELECT columns
     let mut select_columns = vec![
         "e.siret".to_string(),
         "e.siren".to_string(),
+        "e.statut_diffusion".to_string(),
         "e.etat_administratif".to_string(),
         "e.date_creation".to_string(),
         "e.denomination_usuelle".to_string(),
@@ -70,6 +223,8 @@ pub fn search(
         "e.enseigne_3".to_string(),
         "e.code_postal".to_string(),
         "e.libelle_commune".to_string(),
+        "(SELECT c.code_departement FROM cog_commune_courante c WHERE c.code_commune = e.code_commune) AS code_departement".to_string(),
+        "(SELECT c.code_region FROM cog_commune_courante c WHERE c.code_commune = e.code_commune) AS code_region".to_string(),
         "e.activite_principale".to_string(),
         "e.etablissement_siege".to_string(),
         "e.position".to_string(),
@@ -81,19 +236,48 @@ pub fn search(
         select_columns.push("NULL::float8 AS meter_distance".to_string());
     }
 
-    if has_q {
+    if params.q.is_some() {
         select_columns.push("pdb.score(e.siret) AS score".to_string());
-        select_columns.push("NULL::bigint AS total".to_string());
-        select_columns.push(
-            "pdb.agg('{\"value_count\": {\"field\": \"siret\"}}') OVER () AS total_json"
-                .to_string(),
-        );
     } else {
         select_columns.push("NULL::real AS score".to_string());
-        select_columns.push("COUNT(*) OVER() AS total".to_string());
-        select_columns.push("NULL::jsonb AS total_json".to_string());
     }
 
+    match (with_total, params.q.is_some()) {
+        (true, true) => {
+            select_columns.push("NULL::bigint AS total".to_string());
+            select_columns.push(
+                "pdb.agg('{\"value_count\": {\"field\": \"siret\"}}') OVER () AS total_json"
+                    .to_string(),
+            );
+        }
+        (true, false) => {
+            select_columns.push("COUNT(*) OVER() AS total".to_string());
+            select_columns.push("NULL::jsonb AS total_json".to_string());
+        }
+        (false, _) => {
+            select_columns.push("NULL::bigint AS total".to_string());
+            select_columns.push("NULL::jsonb AS total_json".to_string());
+        }
+    }
+
+    match sort_key {
+        Some((sort_expression, _)) => {
+            select_columns.push(format!("{sort_expression}::text AS sort_value"))
+        }
+        None => select_columns.push("NULL::text AS sort_value".to_string()),
+    }
+
+    select_columns
+}
+
+// Placeholders are numbered in the order used by bind_search_filter
+fn build_search_filter(
+    params: &EtablissementSearchParams,
+    keyset: Option<(&EtablissementCursor, (&str, &str), SortDirection)>,
+    policy: DiffusionPolicy,
+) -> SearchFilter {
+    let has_geo = params.lat.is_some() && params.lng.is_some() && params.radius.is_some();
+
     // Build FROM clause
     let mut from_parts = vec!["etablissement e".to_string()];
     let mut param_index = 1u32;
@@ -112,113 +296,110 @@ pub fn search(
     let mut conditions: Vec<String> = Vec::new();
 
     // Text search
-    let q_param_index;
-    if has_q {
-        q_param_index = Some(param_index);
+    if params.q.is_some() {
         conditions.push(format!(
             "(e.search_denomination ||| ${param_index} OR e.libelle_commune ||| ${param_index})"
         ));
         param_index += 1;
-    } else {
-        q_param_index = None;
     }
 
     // Geo filter
-    let radius_param_index;
+    let located = located_condition(policy);
     if has_geo {
-        radius_param_index = Some(param_index);
         conditions.push(format!(
-            "ST_DWithin(e.position, ref_point.pt, ${param_index})"
+            "(ST_DWithin(e.position, ref_point.pt, ${param_index}){located})"
+        ));
+        param_index += 1;
+    }
+    if params.bbox.as_deref().and_then(parse_bbox).is_some() {
+        conditions.push(format!(
+            "(ST_Intersects(e.position, ST_MakeEnvelope(${}, ${}, ${}, ${}, 4326)::geography){located})",
+            param_index,
+            param_index + 1,
+            param_index + 2,
+            param_index + 3
+        ));
+        param_index += 4;
+    }
+    if params.geometry.is_some() {
+        conditions.push(format!(
+            "(ST_Intersects(e.position, ST_SetSRID(ST_GeomFromGeoJSON(${param_index}), 4326)::geography){located})"
         ));
         param_index += 1;
-    } else {
-        radius_param_index = None;
     }
 
     // Field filters
-    let mut field_param_indices: Vec<(String, u32)> = Vec::new();
-
-    if let Some(ref _v) = params.etat_administratif {
+    if params.etat_administratif.is_some() {
         conditions.push(format!("e.etat_administratif = ${param_index}"));
-        field_param_indices.push(("etat_administratif".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.code_postal {
+    if params.code_postal.is_some() {
         conditions.push(format!("e.code_postal = ${param_index}"));
-        field_param_indices.push(("code_postal".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.siren {
+    if params.siren.is_some() {
         conditions.push(format!("e.siren = ${param_index}"));
-        field_param_indices.push(("siren".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.code_commune {
+    if params.code_commune.is_some() {
         conditions.push(format!("e.code_commune = ${param_index}"));
-        field_param_indices.push(("code_commune".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.activite_principale {
-        conditions.push(format!("e.activite_principale = ${param_index}"));
-        field_param_indices.push(("activite_principale".to_string(), param_index));
+    if params.code_departement.is_some() {
+        conditions.push(departement_condition("e", param_index));
+        param_index += 1;
+    }
+    if params.code_region.is_some() {
+        conditions.push(region_condition("e", param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.etablissement_siege {
+    if params.activite_principale.is_some() {
+        conditions.push(naf_rev2_condition("e", param_index));
+        param_index += 1;
+    }
+    if params.activite_principale_naf25.is_some() {
+        conditions.push(naf25_condition("e", param_index));
+        param_index += 1;
+    }
+    if params.etablissement_siege.is_some() {
         conditions.push(format!("e.etablissement_siege = ${param_index}"));
-        field_param_indices.push(("etablissement_siege".to_string(), param_index));
         param_index += 1;
     }
 
-    let _ = param_index; // suppress unused warning
-
-    // Build ORDER BY
-    let sort_field = params.sort.unwrap_or(if has_q {
-        EtablissementSortField::Relevance
-    } else {
-        EtablissementSortField::DateCreation
-    });
-
-    let resolved_dir = params.direction.unwrap_or(match sort_field {
-        EtablissementSortField::Distance => SortDirection::Asc,
-        _ => SortDirection::Desc,
-    });
+    // Keyset pagination
+    if let Some((cursor, (sort_expression, cast), direction)) = keyset {
+        conditions.push(keyset_condition(
+            sort_expression,
+            cast,
+            "e.siret",
+            direction == SortDirection::Asc,
+            cursor.value.is_some(),
+            param_index,
+        ));
+        param_index += if cursor.value.is_some() { 2 } else { 1 };
+    }
 
-    let order_by = match (sort_field, resolved_dir) {
-        (EtablissementSortField::Distance, SortDirection::Desc) => {
-            "e.position <-> ref_point.pt DESC"
-        }
-        (EtablissementSortField::Distance, SortDirection::Asc) => "e.position <-> ref_point.pt ASC",
-        (EtablissementSortField::Relevance, SortDirection::Asc) => "score ASC",
-        (EtablissementSortField::Relevance, SortDirection::Desc) => "score DESC",
-        (EtablissementSortField::DateCreation, SortDirection::Asc) => {
-            "e.date_creation ASC NULLS LAST"
-        }
-        (EtablissementSortField::DateCreation, SortDirection::Desc) => {
-            "e.date_creation DESC NULLS LAST"
-        }
-        (EtablissementSortField::DateDebut, SortDirection::Asc) => "e.date_debut ASC NULLS LAST",
-        (EtablissementSortField::DateDebut, SortDirection::Desc) => "e.date_debut DESC NULLS LAST",
-    };
+    let _ = param_index; // suppress unused warning
 
-    // Assemble query
     let where_clause = if conditions.is_empty() {
         String::new()
     } else {
         format!("WHERE {}", conditions.join(" AND "))
     };
 
-    let sql = format!(
-        "SELECT {} FROM {} {} ORDER BY {} LIMIT {} OFFSET {}",
-        select_columns.join(", "),
-        from_parts.join(", "),
+    SearchFilter {
+        from_clause: from_parts.join(", "),
         where_clause,
-        order_by,
-        limit,
-        offset
-    );
+    }
+}
 
-    // Bind parameters in order
-    let mut query = sql_query(&sql).into_boxed();
+// Bind parameters in the order of build_search_filter placeholders
+fn bind_search_filter<'a>(
+    mut query: BoxedSqlQuery<'a, Pg, SqlQuery>,
+    params: &'a EtablissementSearchParams,
+    cursor: Option<&'a EtablissementCursor>,
+) -> BoxedSqlQuery<'a, Pg, SqlQuery> {
+    let has_geo = params.lat.is_some() && params.lng.is_some() && params.radius.is_some();
 
     if has_geo {
         query = query
@@ -227,57 +408,303 @@ pub fn search(
     }
 
     if let Some(ref q) = params.q {
-        let _ = q_param_index;
         query = query.bind::<Text, _>(q);
     }
 
     if has_geo {
-        let _ = radius_param_index;
         query = query.bind::<Float8, _>(params.radius.unwrap());
     }
+    if let Some(bbox) = params.bbox.as_deref().and_then(parse_bbox) {
+        for value in bbox {
+            query = query.bind::<Float8, _>(value);
+        }
+    }
+    if let Some(ref geometry) = params.geometry {
+        query = query.bind::<Text, _>(geometry);
+    }
 
-    // Bind field filters in order
-    for (field_name, _) in &field_param_indices {
-        match field_name.as_str() {
-            "etat_administratif" => {
-                let val = match params.etat_administratif.unwrap() {
-                    common::EtatAdministratif::A => "A",
-                    common::EtatAdministratif::F => "F",
-                };
-                query = query.bind::<Text, _>(val);
-            }
-            "code_postal" => {
-                query = query.bind::<Text, _>(params.code_postal.as_ref().unwrap());
-            }
-            "siren" => {
-                query = query.bind::<Text, _>(params.siren.as_ref().unwrap());
-            }
-            "code_commune" => {
-                query = query.bind::<Text, _>(params.code_commune.as_ref().unwrap());
-            }
-            "activite_principale" => {
-                query = query.bind::<Text, _>(params.activite_principale.as_ref().unwrap());
-            }
-            "etablissement_siege" => {
-                query = query.bind::<Bool, _>(params.etablissement_siege.unwrap());
-            }
-            _ => {}
+    if let Some(etat_administratif) = params.etat_administratif {
+        let val = match etat_administratif {
+            common::EtatAdministratif::A => "A",
+            common::EtatAdministratif::F => "F",
+        };
+        query = query.bind::<Text, _>(val);
+    }
+    if let Some(ref code_postal) = params.code_postal {
+        query = query.bind::<Text, _>(code_postal);
+    }
+    if let Some(ref siren) = params.siren {
+        query = query.bind::<Text, _>(siren);
+    }
+    if let Some(ref code_commune) = params.code_commune {
+        query = query.bind::<Text, _>(code_commune);
+    }
+    if let Some(ref code_departement) = params.code_departement {
+        query = query.bind::<Text, _>(code_departement);
+    }
+    if let Some(ref code_region) = params.code_region {
+        query = query.bind::<Text, _>(code_region);
+    }
+    if let Some(ref activite_principale) = params.activite_principale {
+        query = query.bind::<Text, _>(activite_principale);
+    }
+    if let Some(ref activite_principale_naf25) = params.activite_principale_naf25 {
+        query = query.bind::<Text, _>(activite_principale_naf25);
+    }
+    if let Some(etablissement_siege) = params.etablissement_siege {
+        query = query.bind::<Bool, _>(etablissement_siege);
+    }
+
+    if let Some(cursor) = cursor {
+        if let Some(ref value) = cursor.value {
+            query = query.bind::<Text, _>(value);
         }
+        query = query.bind::<Text, _>(&cursor.key);
     }
 
-    let results = query
+    query
+}
+
+/// Count the values of each requested facet over the whole filtered set,
+/// ignoring pagination.
+fn search_facets(
+    connection: &mut Connection,
+    params: &EtablissementSearchParams,
+    facets: &[EtablissementFacet],
+    policy: DiffusionPolicy,
+) -> Result<BTreeMap<String, Vec<FacetBucket>>, Error> {
+    let filter = build_search_filter(params, None, policy);
+    let text_search = params.q.is_some();
+
+    facets
+        .iter()
+        .map(|facet| {
+            let sql = facet_query(
+                "e",
+                facet.field(),
+                &filter.from_clause,
+                &filter.where_clause,
+                text_search,
+            );
+            let query = bind_search_filter(sql_query(&sql).into_boxed(), params, None);
+            let buckets = load_facet(connection, query, text_search)?;
+
+            Ok((facet.field().to_string(), buckets))
+        })
+        .collect()
+}
+
+pub fn search(
+    connection: &mut Connection,
+    params: &EtablissementSearchParams,
+    policy: DiffusionPolicy,
+) -> Result<EtablissementSearchOutput, Error> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let facets = match params.facets.as_deref() {
+        Some(facets) => Some(parse_facets(facets).map_err(|name| Error::InvalidFacet { name })?),
+        None => None,
+    };
+
+    // Resolve sort before building the query, keyset pagination depends on it
+    let (sort_field, resolved_dir) = resolve_sort(params);
+    let sort_key = sort_key(sort_field, policy);
+
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => Some(
+            EtablissementCursor::decode(cursor)
+                .filter(|c| c.sort == sort_field && c.direction == resolved_dir)
+                .filter(|_| sort_key.is_some())
+                .ok_or(Error::InvalidCursor)?,
+        ),
+        None => None,
+    };
+
+    let offset = if cursor.is_some() {
+        0
+    } else {
+        params.offset.unwrap_or(0).clamp(0, 10_000)
+    };
+
+    let filter = build_search_filter(
+        params,
+        cursor
+            .as_ref()
+            .zip(sort_key)
+            .map(|(cursor, sort_key)| (cursor, sort_key, resolved_dir)),
+        policy,
+    );
+
+    // Assemble query
+    let sql = format!(
+        "SELECT {} FROM {} {} ORDER BY {} LIMIT {} OFFSET {}",
+        select_columns(params, true, sort_key).join(", "),
+        filter.from_clause,
+        filter.where_clause,
+        order_by(sort_field, resolved_dir, policy),
+        limit,
+        offset
+    );
+
+    let results = bind_search_filter(sql_query(&sql).into_boxed(), params, cursor.as_ref())
         .load::<EtablissementSearchResult>(connection)
         .map_err(|e| -> Error { e.into() })?;
 
+    // Cursors only continue pages fetched by cursor or from the start
+    let next_cursor = match (sort_key, results.last()) {
+        (Some(_), Some(last)) if offset == 0 && results.len() as i64 == limit => Some(
+            EtablissementCursor {
+                sort: sort_field,
+                direction: resolved_dir,
+                value: last.sort_value.clone(),
+                key: last.siret.clone(),
+            }
+            .encode(),
+        ),
+        _ => None,
+    };
+
     Ok(EtablissementSearchOutput {
         results,
         limit,
         offset,
         sort: sort_field,
         direction: resolved_dir,
+        next_cursor,
+        facets: match facets {
+            Some(facets) => Some(search_facets(connection, params, &facets, policy)?),
+            None => None,
+        },
     })
 }
 
+#[derive(QueryableByName)]
+struct Tile {
+    #[diesel(sql_type = Nullable<Bytea>)]
+    tile: Option<Vec<u8>>,
+}
+
+/// Build the Mapbox Vector Tile of the establishments matching the search
+/// filters. Low zoom levels aggregate establishments on a grid and only give
+/// a count for each cell.
+pub fn tile(
+    connection: &mut Connection,
+    params: &EtablissementSearchParams,
+    z: u32,
+    x: u32,
+    y: u32,
+    policy: DiffusionPolicy,
+) -> Result<Vec<u8>, Error> {
+    let filter = build_search_filter(params, None, policy);
+
+    let tile_condition = format!(
+        "ST_Intersects(e.position, ST_Transform(bounds.geom, 4326)::geography){}",
+        located_condition(policy)
+    );
+    let where_clause = if filter.where_clause.is_empty() {
+        format!("WHERE {tile_condition}")
+    } else {
+        format!("{} AND {tile_condition}", filter.where_clause)
+    };
+
+    let features = if z < TILE_CLUSTER_MAX_ZOOM {
+        let cell_size = WEB_MERCATOR_EXTENT / f64::from(1u32 << z) / f64::from(TILE_GRID_CELLS);
+
+        format!(
+            r#"
+            SELECT
+                ST_AsMVTGeom(ST_SnapToGrid(ST_Transform(e.position::geometry, 3857), {cell_size}), bounds.geom) AS geom,
+                COUNT(*) AS count
+            FROM {}, bounds
+            {where_clause}
+            GROUP BY ST_SnapToGrid(ST_Transform(e.position::geometry, 3857), {cell_size}), bounds.geom
+            "#,
+            filter.from_clause
+        )
+    } else {
+        format!(
+            r#"
+            SELECT
+                ST_AsMVTGeom(ST_Transform(e.position::geometry, 3857), bounds.geom) AS geom,
+                e.siret,
+                e.siren,
+                e.etat_administratif,
+                e.denomination_usuelle,
+                e.enseigne_1,
+                e.activite_principale,
+                e.etablissement_siege
+            FROM {}, bounds
+            {where_clause}
+            LIMIT {TILE_MAX_FEATURES}
+            "#,
+            filter.from_clause
+        )
+    };
+
+    let sql = format!(
+        r#"
+        WITH bounds AS (SELECT ST_TileEnvelope({z}, {x}, {y}) AS geom),
+        features AS ({features})
+        SELECT ST_AsMVT(features, 'etablissements', 4096, 'geom') AS tile FROM features
+        "#
+    );
+
+    bind_search_filter(sql_query(&sql).into_boxed(), params, None)
+        .get_result::<Tile>(connection)
+        .map(|tile| tile.tile.unwrap_or_default())
+        .map_err(|error| error.into())
+}
+
+/// Stream every result matching the search filters through a server-side
+/// cursor, `on_batch` returns false to stop the export early.
+pub fn export(
+    connection: &mut Connection,
+    params: &EtablissementSearchParams,
+    policy: DiffusionPolicy,
+    mut on_batch: impl FnMut(Vec<EtablissementSearchResult>) -> bool,
+) -> Result<(), Error> {
+    let filter = build_search_filter(params, None, policy);
+
+    // Sorting the whole set is only done when explicitly asked
+    let order_clause = match params.sort {
+        Some(_) => {
+            let (sort_field, resolved_dir) = resolve_sort(params);
+            format!("ORDER BY {}", order_by(sort_field, resolved_dir, policy))
+        }
+        None => String::new(),
+    };
+
+    let sql = format!(
+        "DECLARE {} NO SCROLL CURSOR FOR SELECT {} FROM {} {} {}",
+        EXPORT_CURSOR,
+        select_columns(params, false, None).join(", "),
+        filter.from_clause,
+        filter.where_clause,
+        order_clause
+    );
+
+    connection
+        .build_transaction()
+        .read_only()
+        .run(|conn| {
+            bind_search_filter(sql_query(&sql).into_boxed(), params, None).execute(conn)?;
+
+            loop {
+                let results = sql_query(format!("FETCH {EXPORT_BATCH_SIZE} FROM {EXPORT_CURSOR}"))
+                    .load::<EtablissementSearchResult>(conn)?;
+
+                let done = results.len() < EXPORT_BATCH_SIZE;
+
+                if results.is_empty() || !on_batch(results) || done {
+                    break;
+                }
+            }
+
+            Ok(())
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+}
+
 pub struct EtablissementModel {}
 
 #[async_trait]
@@ -446,75 +873,169 @@ impl UpdatableModel for EtablissementModel {
             .as_mut()
             .ok_or(UpdatableError::MissingInseeConnector)?;
 
-        let (next_cursor, etablissements) = insee
+        let (next_cursor, etablissements, periodes) = insee
             .get_daily_etablissements(start_timestamp, cursor)
             .await?;
 
         let mut connection = connectors.local.pool.get()?;
 
-        let updated_count = diesel::insert_into(dsl::etablissement)
-            .values(&etablissements)
-            .on_conflict(dsl::siret)
-            .do_update()
-            .set((
-                dsl::nic.eq(excluded(dsl::nic)),
-                dsl::siren.eq(excluded(dsl::siren)),
-                dsl::statut_diffusion.eq(excluded(dsl::statut_diffusion)),
-                dsl::date_creation.eq(excluded(dsl::date_creation)),
-                dsl::tranche_effectifs.eq(excluded(dsl::tranche_effectifs)),
-                dsl::annee_effectifs.eq(excluded(dsl::annee_effectifs)),
-                dsl::activite_principale_registre_metiers
-                    .eq(excluded(dsl::activite_principale_registre_metiers)),
-                dsl::date_dernier_traitement.eq(excluded(dsl::date_dernier_traitement)),
-                dsl::etablissement_siege.eq(excluded(dsl::etablissement_siege)),
-                dsl::nombre_periodes.eq(excluded(dsl::nombre_periodes)),
-                dsl::complement_adresse.eq(excluded(dsl::complement_adresse)),
-                dsl::numero_voie.eq(excluded(dsl::numero_voie)),
-                dsl::indice_repetition.eq(excluded(dsl::indice_repetition)),
-                dsl::type_voie.eq(excluded(dsl::type_voie)),
-                dsl::libelle_voie.eq(excluded(dsl::libelle_voie)),
-                dsl::code_postal.eq(excluded(dsl::code_postal)),
-                dsl::libelle_commune.eq(excluded(dsl::libelle_commune)),
-                dsl::libelle_commune_etranger.eq(excluded(dsl::libelle_commune_etranger)),
-                dsl::distribution_speciale.eq(excluded(dsl::distribution_speciale)),
-                dsl::code_commune.eq(excluded(dsl::code_commune)),
-                dsl::code_cedex.eq(excluded(dsl::code_cedex)),
-                dsl::libelle_cedex.eq(excluded(dsl::libelle_cedex)),
-                dsl::code_pays_etranger.eq(excluded(dsl::code_pays_etranger)),
-                dsl::libelle_pays_etranger.eq(excluded(dsl::libelle_pays_etranger)),
-                dsl::complement_adresse2.eq(excluded(dsl::complement_adresse2)),
-                dsl::numero_voie_2.eq(excluded(dsl::numero_voie_2)),
-                dsl::indice_repetition_2.eq(excluded(dsl::indice_repetition_2)),
-                dsl::type_voie_2.eq(excluded(dsl::type_voie_2)),
-                dsl::libelle_voie_2.eq(excluded(dsl::libelle_voie_2)),
-                dsl::code_postal_2.eq(excluded(dsl::code_postal_2)),
-                dsl::libelle_commune_2.eq(excluded(dsl::libelle_commune_2)),
-                dsl::libelle_commune_etranger_2.eq(excluded(dsl::libelle_commune_etranger_2)),
-                dsl::distribution_speciale_2.eq(excluded(dsl::distribution_speciale_2)),
-                dsl::code_commune_2.eq(excluded(dsl::code_commune_2)),
-                dsl::code_cedex_2.eq(excluded(dsl::code_cedex_2)),
-                dsl::libelle_cedex_2.eq(excluded(dsl::libelle_cedex_2)),
-                dsl::code_pays_etranger_2.eq(excluded(dsl::code_pays_etranger_2)),
-                dsl::libelle_pays_etranger_2.eq(excluded(dsl::libelle_pays_etranger_2)),
-                dsl::date_debut.eq(excluded(dsl::date_debut)),
-                dsl::etat_administratif.eq(excluded(dsl::etat_administratif)),
-                dsl::enseigne_1.eq(excluded(dsl::enseigne_1)),
-                dsl::enseigne_2.eq(excluded(dsl::enseigne_2)),
-                dsl::enseigne_3.eq(excluded(dsl::enseigne_3)),
-                dsl::denomination_usuelle.eq(excluded(dsl::denomination_usuelle)),
-                dsl::activite_principale.eq(excluded(dsl::activite_principale)),
-                dsl::nomenclature_activite_principale
-                    .eq(excluded(dsl::nomenclature_activite_principale)),
-                dsl::caractere_employeur.eq(excluded(dsl::caractere_employeur)),
-                dsl::dernier_numero_voie.eq(excluded(dsl::dernier_numero_voie)),
-                dsl::indice_repetition_dernier_numero_voie
-                    .eq(excluded(dsl::indice_repetition_dernier_numero_voie)),
-                dsl::identifiant_adresse.eq(excluded(dsl::identifiant_adresse)),
-                dsl::coordonnee_lambert_x.eq(excluded(dsl::coordonnee_lambert_x)),
-                dsl::coordonnee_lambert_y.eq(excluded(dsl::coordonnee_lambert_y)),
-            ))
-            .execute(&mut connection)?;
+        let updated_count = connection.build_transaction().read_write().run(|conn| {
+            let updated_count = diesel::insert_into(dsl::etablissement)
+                .values(&etablissements)
+                .on_conflict(dsl::siret)
+                .do_update()
+                .set((
+                    dsl::nic.eq(excluded(dsl::nic)),
+                    dsl::siren.eq(excluded(dsl::siren)),
+                    dsl::statut_diffusion.eq(excluded(dsl::statut_diffusion)),
+                    dsl::date_creation.eq(excluded(dsl::date_creation)),
+                    dsl::tranche_effectifs.eq(excluded(dsl::tranche_effectifs)),
+                    dsl::annee_effectifs.eq(excluded(dsl::annee_effectifs)),
+                    dsl::activite_principale_registre_metiers
+                        .eq(excluded(dsl::activite_principale_registre_metiers)),
+                    dsl::date_dernier_traitement.eq(excluded(dsl::date_dernier_traitement)),
+                    dsl::etablissement_siege.eq(excluded(dsl::etablissement_siege)),
+                    dsl::nombre_periodes.eq(excluded(dsl::nombre_periodes)),
+                    dsl::complement_adresse.eq(excluded(dsl::complement_adresse)),
+                    dsl::numero_voie.eq(excluded(dsl::numero_voie)),
+                    dsl::indice_repetition.eq(excluded(dsl::indice_repetition)),
+                    dsl::type_voie.eq(excluded(dsl::type_voie)),
+                    dsl::libelle_voie.eq(excluded(dsl::libelle_voie)),
+                    dsl::code_postal.eq(excluded(dsl::code_postal)),
+                    dsl::libelle_commune.eq(excluded(dsl::libelle_commune)),
+                    dsl::libelle_commune_etranger.eq(excluded(dsl::libelle_commune_etranger)),
+                    dsl::distribution_speciale.eq(excluded(dsl::distribution_speciale)),
+                    dsl::code_commune.eq(excluded(dsl::code_commune)),
+                    dsl::code_cedex.eq(excluded(dsl::code_cedex)),
+                    dsl::libelle_cedex.eq(excluded(dsl::libelle_cedex)),
+                    dsl::code_pays_etranger.eq(excluded(dsl::code_pays_etranger)),
+                    dsl::libelle_pays_etranger.eq(excluded(dsl::libelle_pays_etranger)),
+                    dsl::complement_adresse2.eq(excluded(dsl::complement_adresse2)),
+                    dsl::numero_voie_2.eq(excluded(dsl::numero_voie_2)),
+                    dsl::indice_repetition_2.eq(excluded(dsl::indice_repetition_2)),
+                    dsl::type_voie_2.eq(excluded(dsl::type_voie_2)),
+                    dsl::libelle_voie_2.eq(excluded(dsl::libelle_voie_2)),
+                    dsl::code_postal_2.eq(excluded(dsl::code_postal_2)),
+                    dsl::libelle_commune_2.eq(excluded(dsl::libelle_commune_2)),
+                    dsl::libelle_commune_etranger_2.eq(excluded(dsl::libelle_commune_etranger_2)),
+                    dsl::distribution_speciale_2.eq(excluded(dsl::distribution_speciale_2)),
+                    dsl::code_commune_2.eq(excluded(dsl::code_commune_2)),
+                    dsl::code_cedex_2.eq(excluded(dsl::code_cedex_2)),
+                    dsl::libelle_cedex_2.eq(excluded(dsl::libelle_cedex_2)),
+                    dsl::code_pays_etranger_2.eq(excluded(dsl::code_pays_etranger_2)),
+                    dsl::libelle_pays_etranger_2.eq(excluded(dsl::libelle_pays_etranger_2)),
+                    dsl::date_debut.eq(excluded(dsl::date_debut)),
+                    dsl::etat_administratif.eq(excluded(dsl::etat_administratif)),
+                    dsl::enseigne_1.eq(excluded(dsl::enseigne_1)),
+                    dsl::enseigne_2.eq(excluded(dsl::enseigne_2)),
+                    dsl::enseigne_3.eq(excluded(dsl::enseigne_3)),
+                    dsl::denomination_usuelle.eq(excluded(dsl::denomination_usuelle)),
+                    dsl::activite_principale.eq(excluded(dsl::activite_principale)),
+                    dsl::nomenclature_activite_principale
+                        .eq(excluded(dsl::nomenclature_activite_principale)),
+                    dsl::caractere_employeur.eq(excluded(dsl::caractere_employeur)),
+                    dsl::activite_principale_naf25.eq(excluded(dsl::activite_principale_naf25)),
+                    dsl::dernier_numero_voie.eq(excluded(dsl::dernier_numero_voie)),
+                    dsl::indice_repetition_dernier_numero_voie
+                        .eq(excluded(dsl::indice_repetition_dernier_numero_voie)),
+                    dsl::identifiant_adresse.eq(excluded(dsl::identifiant_adresse)),
+                    dsl::coordonnee_lambert_x.eq(excluded(dsl::coordonnee_lambert_x)),
+                    dsl::coordonnee_lambert_y.eq(excluded(dsl::coordonnee_lambert_y)),
+                ))
+                .execute(conn)?;
+
+            super::etablissement_periode::replace(conn, &periodes)?;
+
+            diesel::QueryResult::Ok(updated_count)
+        })?;
 
         Ok((next_cursor, updated_count))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HIDDEN: &str = "e.statut_diffusion <> 'P'";
+
+    fn radius_search() -> EtablissementSearchParams {
+        EtablissementSearchParams {
+            lat: Some(48.85),
+            lng: Some(2.35),
+            radius: Some(10.0),
+            ..Default::default()
+        }
+    }
+
+    fn bbox_search() -> EtablissementSearchParams {
+        EtablissementSearchParams {
+            bbox: Some("2.34,48.84,2.36,48.86".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn geometry_search() -> EtablissementSearchParams {
+        EtablissementSearchParams {
+            geometry: Some(
+                r#"{"type":"Polygon","coordinates":[[[2.34,48.84],[2.36,48.84],[2.36,48.86],[2.34,48.84]]]}"#
+                    .to_string(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn public_geo_filters_exclude_partially_diffused_establishments() {
+        for (params, function) in [
+            (radius_search(), "ST_DWithin"),
+            (bbox_search(), "ST_MakeEnvelope"),
+            (geometry_search(), "ST_GeomFromGeoJSON"),
+        ] {
+            let filter = build_search_filter(&params, None, DiffusionPolicy::Public);
+            let condition = filter
+                .where_clause
+                .split(" AND (")
+                .find(|condition| condition.contains(function))
+                .unwrap_or_else(|| panic!("{function} condition missing"));
+
+            assert!(condition.contains(HIDDEN), "{condition}");
+        }
+    }
+
+    #[test]
+    fn full_diffusion_geo_filters_include_every_establishment() {
+        for params in [radius_search(), bbox_search(), geometry_search()] {
+            let filter = build_search_filter(&params, None, DiffusionPolicy::Raw);
+
+            assert!(
+                !filter.where_clause.contains(HIDDEN),
+                "{}",
+                filter.where_clause
+            );
+        }
+    }
+
+    #[test]
+    fn public_distance_sort_ignores_partially_diffused_positions() {
+        for direction in [SortDirection::Asc, SortDirection::Desc] {
+            let public = order_by(
+                EtablissementSortField::Distance,
+                direction,
+                DiffusionPolicy::Public,
+            );
+            let raw = order_by(
+                EtablissementSortField::Distance,
+                direction,
+                DiffusionPolicy::Raw,
+            );
+
+            assert!(public.contains(HIDDEN), "{public}");
+            assert!(!raw.contains(HIDDEN), "{raw}");
+        }
+
+        let (expression, _) =
+            sort_key(EtablissementSortField::Distance, DiffusionPolicy::Public).unwrap();
+        assert!(expression.contains(HIDDEN), "{expression}");
+    }
+}
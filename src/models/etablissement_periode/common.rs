@@ -0,0 +1,51 @@
+use super::super::etablissement::common::Etablissement;
+use super::super::schema::etablissement_periode;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// State of an establishment between two changes, the `changement_*` flags
+/// telling which values changed at `date_debut`
+#[derive(Insertable, Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
+#[diesel(table_name = etablissement_periode)]
+pub struct EtablissementPeriode {
+    pub siren: String,
+    pub nic: String,
+    pub siret: String,
+    pub date_fin: Option<NaiveDate>,
+    pub date_debut: Option<NaiveDate>,
+    pub etat_administratif: String,
+    pub changement_etat_administratif: bool,
+    pub enseigne_1: Option<String>,
+    pub enseigne_2: Option<String>,
+    pub enseigne_3: Option<String>,
+    pub changement_enseigne: bool,
+    pub denomination_usuelle: Option<String>,
+    pub changement_denomination_usuelle: bool,
+    pub activite_principale: Option<String>,
+    pub nomenclature_activite_principale: Option<String>,
+    pub changement_activite_principale: bool,
+    pub caractere_employeur: Option<String>,
+    pub changement_caractere_employeur: bool,
+}
+
+impl EtablissementPeriode {
+    /// Replace the values of `etablissement` varying with periods
+    pub fn apply_to(self, etablissement: &mut Etablissement) {
+        // NAF 2025 codes are only known for the current period
+        if self.date_fin.is_some() {
+            etablissement.activite_principale_naf25 = None;
+        }
+
+        etablissement.date_debut = self.date_debut;
+        etablissement.etat_administratif = self.etat_administratif;
+        etablissement.enseigne_1 = self.enseigne_1;
+        etablissement.enseigne_2 = self.enseigne_2;
+        etablissement.enseigne_3 = self.enseigne_3;
+        etablissement.denomination_usuelle = self.denomination_usuelle;
+        etablissement.activite_principale = self.activite_principale;
+        etablissement.nomenclature_activite_principale = self.nomenclature_activite_principale;
+        etablissement.caractere_employeur = self.caractere_employeur;
+    }
+}
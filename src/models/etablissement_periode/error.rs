@@ -0,0 +1,8 @@
+use chrono::NaiveDate;
+use custom_error::custom_error;
+
+custom_error! { pub Error
+    NotExisting{date: NaiveDate} = "Establishment not existing on {date}.",
+    LocalConnectionFailed{source: r2d2::Error} = "Unable to connect to local database ({source}).",
+    Database{source: diesel::result::Error} = "Unable to run some operations on etablissement_periode ({source}).",
+}
@@ -0,0 +1,273 @@
+pub mod common;
+pub mod error;
+
+use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::etablissement::common::Etablissement;
+use super::schema::etablissement_periode::dsl;
+use crate::connectors::{Connectors, local::Connection};
+use crate::update::utils::remote_file::RemoteFile;
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
+use common::EtablissementPeriode;
+use diesel::pg::{CopyFormat, CopyHeader, PgConnection};
+use diesel::prelude::*;
+use diesel::sql_query;
+use error::Error;
+use std::collections::HashMap;
+
+// Keeps the bound parameters of an insert below the PostgreSQL limit
+const INSERT_CHUNK_SIZE: usize = 2000;
+
+/// Periods of an establishment, the most recent first
+pub fn get(connection: &mut Connection, siret: &str) -> Result<Vec<EtablissementPeriode>, Error> {
+    dsl::etablissement_periode
+        .select(EtablissementPeriode::as_select())
+        .filter(dsl::siret.eq(siret))
+        .order(dsl::date_debut.desc().nulls_last())
+        .load::<EtablissementPeriode>(connection)
+        .map_err(|error| error.into())
+}
+
+/// Rebuild the establishment as it was on `date`, from the period valid then
+pub fn as_of(
+    connection: &mut Connection,
+    mut etablissement: Etablissement,
+    date: NaiveDate,
+) -> Result<Etablissement, Error> {
+    if etablissement
+        .date_creation
+        .is_some_and(|date_creation| date_creation > date)
+    {
+        return Err(Error::NotExisting { date });
+    }
+
+    let periode = dsl::etablissement_periode
+        .select(EtablissementPeriode::as_select())
+        .filter(dsl::siret.eq(&etablissement.siret))
+        .filter(dsl::date_debut.is_null().or(dsl::date_debut.le(date)))
+        .filter(dsl::date_fin.is_null().or(dsl::date_fin.ge(date)))
+        .order(dsl::date_debut.desc().nulls_last())
+        .first::<EtablissementPeriode>(connection)
+        .optional()?
+        .ok_or(Error::NotExisting { date })?;
+
+    periode.apply_to(&mut etablissement);
+
+    Ok(etablissement)
+}
+
+/// Rebuild the establishments as they were on `date`, leaving out the ones
+/// not existing then
+pub fn all_as_of(
+    connection: &mut Connection,
+    etablissements: Vec<Etablissement>,
+    date: NaiveDate,
+) -> Result<Vec<Etablissement>, Error> {
+    let etablissements: Vec<Etablissement> = etablissements
+        .into_iter()
+        .filter(|e| {
+            e.date_creation
+                .is_none_or(|date_creation| date_creation <= date)
+        })
+        .collect();
+    let sirets: Vec<&str> = etablissements.iter().map(|e| e.siret.as_str()).collect();
+
+    let mut periodes: HashMap<String, EtablissementPeriode> = HashMap::new();
+    for periode in dsl::etablissement_periode
+        .select(EtablissementPeriode::as_select())
+        .filter(dsl::siret.eq_any(&sirets))
+        .filter(dsl::date_debut.is_null().or(dsl::date_debut.le(date)))
+        .filter(dsl::date_fin.is_null().or(dsl::date_fin.ge(date)))
+        .order((dsl::siret, dsl::date_debut.desc().nulls_last()))
+        .load::<EtablissementPeriode>(connection)?
+    {
+        periodes.entry(periode.siret.clone()).or_insert(periode);
+    }
+
+    Ok(etablissements
+        .into_iter()
+        .filter_map(|mut etablissement| {
+            periodes.remove(&etablissement.siret).map(|periode| {
+                periode.apply_to(&mut etablissement);
+                etablissement
+            })
+        })
+        .collect())
+}
+
+/// Number of establishments of a SIREN for each etat_administratif on `date`
+pub fn count_with_siren_as_of(
+    connection: &mut Connection,
+    siren: &str,
+    date: NaiveDate,
+) -> Result<Vec<(String, i64)>, Error> {
+    use super::schema::etablissement;
+
+    let existing = etablissement::table
+        .select(etablissement::siret)
+        .filter(etablissement::siren.eq(siren))
+        .filter(
+            etablissement::date_creation
+                .is_null()
+                .or(etablissement::date_creation.le(date)),
+        );
+
+    dsl::etablissement_periode
+        .filter(dsl::siret.eq_any(existing))
+        .filter(dsl::date_debut.is_null().or(dsl::date_debut.le(date)))
+        .filter(dsl::date_fin.is_null().or(dsl::date_fin.ge(date)))
+        .group_by(dsl::etat_administratif)
+        .select((
+            dsl::etat_administratif,
+            diesel::dsl::count(dsl::siret).aggregate_distinct(),
+        ))
+        .load::<(String, i64)>(connection)
+        .map_err(|error| error.into())
+}
+
+/// Replace the whole history of the establishments the periods belong to, as the
+/// INSEE API always returns every period of an establishment
+pub fn replace(
+    connection: &mut PgConnection,
+    periodes: &[EtablissementPeriode],
+) -> QueryResult<usize> {
+    let mut sirets: Vec<&String> = periodes.iter().map(|p| &p.siret).collect();
+    sirets.sort_unstable();
+    sirets.dedup();
+
+    diesel::delete(dsl::etablissement_periode.filter(dsl::siret.eq_any(sirets)))
+        .execute(connection)?;
+
+    let mut inserted_count = 0;
+    for chunk in periodes.chunks(INSERT_CHUNK_SIZE) {
+        inserted_count += diesel::insert_into(dsl::etablissement_periode)
+            .values(chunk)
+            .execute(connection)?;
+    }
+
+    Ok(inserted_count)
+}
+
+pub struct EtablissementPeriodeModel {}
+
+#[async_trait]
+impl UpdatableModel for EtablissementPeriodeModel {
+    fn count(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        dsl::etablissement_periode
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn count_staging(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        use super::schema::etablissement_periode_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+        dsl::etablissement_periode_staging
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn insert_remote_file_in_staging(
+        &self,
+        connectors: &Connectors,
+        remote_file: RemoteFile,
+    ) -> Result<bool, UpdatableError> {
+        use super::schema::etablissement_periode_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+
+        sql_query("TRUNCATE etablissement_periode_staging").execute(&mut connection)?;
+
+        diesel::copy_from(dsl::etablissement_periode_staging)
+            .from_raw_data(
+                (
+                    dsl::siren,
+                    dsl::nic,
+                    dsl::siret,
+                    dsl::date_fin,
+                    dsl::date_debut,
+                    dsl::etat_administratif,
+                    dsl::changement_etat_administratif,
+                    dsl::enseigne_1,
+                    dsl::enseigne_2,
+                    dsl::enseigne_3,
+                    dsl::changement_enseigne,
+                    dsl::denomination_usuelle,
+                    dsl::changement_denomination_usuelle,
+                    dsl::activite_principale,
+                    dsl::nomenclature_activite_principale,
+                    dsl::changement_activite_principale,
+                    dsl::caractere_employeur,
+                    dsl::changement_caractere_employeur,
+                ),
+                |write| copy_remote_zipped_csv(remote_file.to_reader(), write),
+            )
+            .with_delimiter(',')
+            .with_format(CopyFormat::Csv)
+            .with_header(CopyHeader::Set(true))
+            .execute(&mut connection)
+            .map(|count| count > 0)
+            .map_err(|error| error.into())
+    }
+
+    fn swap(&self, connectors: &Connectors) -> Result<(), UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        connection.build_transaction().read_write().run(|conn| {
+            sql_query("ALTER TABLE etablissement_periode RENAME TO etablissement_periode_temp")
+                .execute(conn)?;
+            sql_query("ALTER TABLE etablissement_periode_staging RENAME TO etablissement_periode")
+                .execute(conn)?;
+            sql_query(
+                "ALTER TABLE etablissement_periode_temp RENAME TO etablissement_periode_staging",
+            )
+            .execute(conn)?;
+            sql_query("TRUNCATE etablissement_periode_staging").execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET last_imported_timestamp = staging_imported_timestamp
+                WHERE group_type = 'etablissements_periodes'
+                "#,
+            )
+            .execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET staging_imported_timestamp = NULL
+                WHERE group_type = 'etablissements_periodes'
+                "#,
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    // Periods are synced along with their establishment, see `replace`
+    async fn get_total_count(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+    ) -> Result<u32, UpdatableError> {
+        Ok(0)
+    }
+
+    fn get_last_insee_synced_timestamp(
+        &self,
+        _connectors: &Connectors,
+    ) -> Result<Option<NaiveDateTime>, UpdatableError> {
+        Ok(None)
+    }
+
+    async fn update_daily_data(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+        _cursor: String,
+    ) -> Result<(Option<String>, usize), UpdatableError> {
+        Ok((None, 0))
+    }
+}
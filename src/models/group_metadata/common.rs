@@ -1,8 +1,13 @@
+use super::super::cog::CogModel;
 use super::super::common::UpdatableModel;
 use super::super::etablissement::EtablissementModel;
+use super::super::etablissement_periode::EtablissementPeriodeModel;
 use super::super::lien_succession::LienSuccessionModel;
+use super::super::naf_correspondance::NafCorrespondanceModel;
+use super::super::nomenclature::NomenclatureModel;
 use super::super::schema::group_metadata;
 use super::super::unite_legale::UniteLegaleModel;
+use super::super::unite_legale_periode::UniteLegalePeriodeModel;
 use chrono::{DateTime, Utc};
 use diesel::deserialize::{self, FromSql};
 use diesel::pg::{Pg, PgValue};
@@ -44,6 +49,11 @@ pub enum GroupType {
     UnitesLegales,
     Etablissements,
     LiensSuccession,
+    UnitesLegalesPeriodes,
+    EtablissementsPeriodes,
+    NafCorrespondances,
+    Nomenclatures,
+    Cog,
 }
 
 impl GroupType {
@@ -52,8 +62,30 @@ impl GroupType {
             GroupType::UnitesLegales => Box::new(UniteLegaleModel {}),
             GroupType::Etablissements => Box::new(EtablissementModel {}),
             GroupType::LiensSuccession => Box::new(LienSuccessionModel {}),
+            GroupType::UnitesLegalesPeriodes => Box::new(UniteLegalePeriodeModel {}),
+            GroupType::EtablissementsPeriodes => Box::new(EtablissementPeriodeModel {}),
+            GroupType::NafCorrespondances => Box::new(NafCorrespondanceModel {}),
+            GroupType::Nomenclatures => Box::new(NomenclatureModel {}),
+            GroupType::Cog => Box::new(CogModel {}),
         }
     }
+
+    /// Group whose INSEE sync also fills this one
+    pub fn synced_with(&self) -> Option<GroupType> {
+        match self {
+            GroupType::UnitesLegalesPeriodes => Some(GroupType::UnitesLegales),
+            GroupType::EtablissementsPeriodes => Some(GroupType::Etablissements),
+            _ => None,
+        }
+    }
+
+    /// Whether the group is kept up to date by the INSEE API between stocks
+    pub fn insee_synced(&self) -> bool {
+        !matches!(
+            self,
+            GroupType::NafCorrespondances | GroupType::Nomenclatures | GroupType::Cog
+        )
+    }
 }
 
 // SQL conversion
@@ -63,6 +95,11 @@ impl ToSql<Text, Pg> for GroupType {
             GroupType::UnitesLegales => out.write_all(b"unites_legales")?,
             GroupType::Etablissements => out.write_all(b"etablissements")?,
             GroupType::LiensSuccession => out.write_all(b"liens_succession")?,
+            GroupType::UnitesLegalesPeriodes => out.write_all(b"unites_legales_periodes")?,
+            GroupType::EtablissementsPeriodes => out.write_all(b"etablissements_periodes")?,
+            GroupType::NafCorrespondances => out.write_all(b"naf_correspondances")?,
+            GroupType::Nomenclatures => out.write_all(b"nomenclatures")?,
+            GroupType::Cog => out.write_all(b"cog")?,
         }
         Ok(IsNull::No)
     }
@@ -74,6 +111,11 @@ impl FromSql<Text, Pg> for GroupType {
             b"unites_legales" => Ok(GroupType::UnitesLegales),
             b"etablissements" => Ok(GroupType::Etablissements),
             b"liens_succession" => Ok(GroupType::LiensSuccession),
+            b"unites_legales_periodes" => Ok(GroupType::UnitesLegalesPeriodes),
+            b"etablissements_periodes" => Ok(GroupType::EtablissementsPeriodes),
+            b"naf_correspondances" => Ok(GroupType::NafCorrespondances),
+            b"nomenclatures" => Ok(GroupType::Nomenclatures),
+            b"cog" => Ok(GroupType::Cog),
             _ => Err("Unrecognized enum variant".into()),
         }
     }
@@ -85,6 +127,11 @@ impl std::fmt::Display for GroupType {
             GroupType::UnitesLegales => write!(f, "unités légales"),
             GroupType::Etablissements => write!(f, "établissements"),
             GroupType::LiensSuccession => write!(f, "liens de succession"),
+            GroupType::UnitesLegalesPeriodes => write!(f, "périodes des unités légales"),
+            GroupType::EtablissementsPeriodes => write!(f, "périodes des établissements"),
+            GroupType::NafCorrespondances => write!(f, "correspondances NAF"),
+            GroupType::Nomenclatures => write!(f, "nomenclatures"),
+            GroupType::Cog => write!(f, "code officiel géographique"),
         }
     }
 }
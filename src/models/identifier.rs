@@ -0,0 +1,285 @@
+use custom_error::custom_error;
+
+const SIREN_LENGTH: usize = 9;
+const SIRET_LENGTH: usize = 14;
+// La Poste establishments share this SIREN and are numbered beyond what the
+// Luhn algorithm allows, their digits sum to a multiple of 5 instead.
+const LA_POSTE_SIREN: &str = "356000000";
+
+custom_error! { pub IdentifierError
+    NotDigits{kind: &'static str, value: String} = "{kind} {value} must only contain digits",
+    WrongLength{kind: &'static str, value: String, expected: usize} = "{kind} {value} must have {expected} digits",
+    LuhnChecksum{kind: &'static str, value: String} = "{kind} {value} fails the Luhn checksum",
+    LaPosteChecksum{value: String} = "SIRET {value} of La Poste must have a sum of digits divisible by 5",
+    TvaFormat{value: String} = "TVA number {value} must be FR followed by a 2 digit key and a SIREN",
+    TvaKey{value: String, expected: String} = "TVA number {value} has a wrong key, expected {expected}",
+}
+
+/// Remove the spaces and dots used to format identifiers, e.g. `123 456 789`
+/// or `123.456.789`.
+pub fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '.')
+        .collect()
+}
+
+fn digits(kind: &'static str, value: String, expected: usize) -> Result<String, IdentifierError> {
+    if !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(IdentifierError::NotDigits { kind, value });
+    }
+
+    if value.len() != expected {
+        return Err(IdentifierError::WrongLength {
+            kind,
+            value,
+            expected,
+        });
+    }
+
+    Ok(value)
+}
+
+fn digit_values(value: &str) -> impl DoubleEndedIterator<Item = u32> + '_ {
+    value.bytes().map(|b| u32::from(b - b'0'))
+}
+
+/// Luhn checksum, every second digit from the right being doubled
+fn is_luhn_valid(value: &str) -> bool {
+    let sum: u32 = digit_values(value)
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| match index % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+/// Normalize and validate a SIREN
+pub fn parse_siren(value: &str) -> Result<String, IdentifierError> {
+    let siren = digits("SIREN", normalize(value), SIREN_LENGTH)?;
+
+    if !is_luhn_valid(&siren) {
+        return Err(IdentifierError::LuhnChecksum {
+            kind: "SIREN",
+            value: siren,
+        });
+    }
+
+    Ok(siren)
+}
+
+/// Normalize and validate a SIRET, including the La Poste exception
+pub fn parse_siret(value: &str) -> Result<String, IdentifierError> {
+    let siret = digits("SIRET", normalize(value), SIRET_LENGTH)?;
+
+    if siret.starts_with(LA_POSTE_SIREN) {
+        if !digit_values(&siret).sum::<u32>().is_multiple_of(5) {
+            return Err(IdentifierError::LaPosteChecksum { value: siret });
+        }
+    } else if !is_luhn_valid(&siret) {
+        return Err(IdentifierError::LuhnChecksum {
+            kind: "SIRET",
+            value: siret,
+        });
+    }
+
+    Ok(siret)
+}
+
+/// Key of the French intra-community VAT number of a valid SIREN
+fn tva_key(siren: &str) -> String {
+    let siren: u64 = siren.parse().unwrap_or_default();
+
+    format!("{:02}", (12 + 3 * (siren % 97)) % 97)
+}
+
+/// Normalize and validate a French intra-community VAT number, returning its SIREN
+pub fn parse_numero_tva(value: &str) -> Result<String, IdentifierError> {
+    let numero = normalize(value).to_uppercase();
+
+    let Some((key, siren)) = numero
+        .strip_prefix("FR")
+        .filter(|rest| rest.len() == 2 + SIREN_LENGTH && rest.is_char_boundary(2))
+        .map(|rest| rest.split_at(2))
+        .filter(|(key, _)| key.chars().all(|c| c.is_ascii_digit()))
+    else {
+        return Err(IdentifierError::TvaFormat { value: numero });
+    };
+
+    let siren = parse_siren(siren)?;
+    let expected = tva_key(&siren);
+
+    if key != expected {
+        return Err(IdentifierError::TvaKey {
+            value: numero,
+            expected,
+        });
+    }
+
+    Ok(siren)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_sirens() {
+        assert_eq!(parse_siren("404833048").unwrap(), "404833048");
+        assert_eq!(parse_siren("356000000").unwrap(), "356000000");
+    }
+
+    #[test]
+    fn sirens_with_separators() {
+        for value in [
+            "404 833 048",
+            "404.833.048",
+            " 404833048\t",
+            "404\u{a0}833\u{a0}048",
+        ] {
+            assert_eq!(parse_siren(value).unwrap(), "404833048", "{value:?}");
+        }
+    }
+
+    #[test]
+    fn sirens_failing_the_luhn_checksum() {
+        assert!(matches!(
+            parse_siren("404833049"),
+            Err(IdentifierError::LuhnChecksum { kind: "SIREN", value }) if value == "404833049"
+        ));
+    }
+
+    #[test]
+    fn sirens_of_the_wrong_length() {
+        for value in ["40483304", "4048330480", ""] {
+            assert!(
+                matches!(
+                    parse_siren(value),
+                    Err(IdentifierError::WrongLength {
+                        kind: "SIREN",
+                        expected: SIREN_LENGTH,
+                        ..
+                    })
+                ),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sirens_with_other_characters() {
+        for value in ["40483304A", "404-833-048", "４04833048"] {
+            assert!(
+                matches!(
+                    parse_siren(value),
+                    Err(IdentifierError::NotDigits { kind: "SIREN", .. })
+                ),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn valid_sirets() {
+        assert_eq!(parse_siret("40483304800022").unwrap(), "40483304800022");
+        assert_eq!(parse_siret("404 833 048 00022").unwrap(), "40483304800022");
+        assert_eq!(parse_siret("404.833.048.00022").unwrap(), "40483304800022");
+    }
+
+    #[test]
+    fn invalid_sirets() {
+        assert!(matches!(
+            parse_siret("40483304800023"),
+            Err(IdentifierError::LuhnChecksum { kind: "SIRET", value }) if value == "40483304800023"
+        ));
+        assert!(matches!(
+            parse_siret("404833048"),
+            Err(IdentifierError::WrongLength {
+                kind: "SIRET",
+                expected: SIRET_LENGTH,
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse_siret("4048330480002X"),
+            Err(IdentifierError::NotDigits { kind: "SIRET", .. })
+        ));
+    }
+
+    #[test]
+    fn la_poste_sirets_use_the_sum_of_their_digits() {
+        // Sums to 15 but fails the Luhn checksum
+        assert!(!is_luhn_valid("35600000000001"));
+        assert_eq!(parse_siret("35600000000001").unwrap(), "35600000000001");
+        assert_eq!(parse_siret("356 000 000 00001").unwrap(), "35600000000001");
+
+        // Passes the Luhn checksum but sums to 19
+        assert!(is_luhn_valid("35600000000014"));
+        assert!(matches!(
+            parse_siret("35600000000014"),
+            Err(IdentifierError::LaPosteChecksum { value }) if value == "35600000000014"
+        ));
+    }
+
+    #[test]
+    fn tva_keys() {
+        assert_eq!(tva_key("404833048"), "83");
+        assert_eq!(tva_key("100002146"), "00");
+    }
+
+    #[test]
+    fn valid_numeros_tva() {
+        for value in [
+            "FR83404833048",
+            "fr83404833048",
+            "FR 83 404 833 048",
+            "FR00100002146",
+        ] {
+            assert!(parse_numero_tva(value).is_ok(), "{value:?}");
+        }
+        assert_eq!(parse_numero_tva("FR83404833048").unwrap(), "404833048");
+        assert_eq!(parse_numero_tva("FR00100002146").unwrap(), "100002146");
+    }
+
+    #[test]
+    fn numeros_tva_with_a_wrong_key() {
+        assert!(matches!(
+            parse_numero_tva("FR84404833048"),
+            Err(IdentifierError::TvaKey { value, expected })
+                if value == "FR84404833048" && expected == "83"
+        ));
+    }
+
+    #[test]
+    fn numeros_tva_with_a_wrong_format() {
+        for value in [
+            "DE83404833048",
+            "83404833048",
+            "FR8340483304",
+            "FR834048330480",
+            "FRAB404833048",
+            "FR8é404833048",
+        ] {
+            assert!(
+                matches!(
+                    parse_numero_tva(value),
+                    Err(IdentifierError::TvaFormat { .. })
+                ),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn numeros_tva_with_an_invalid_siren() {
+        assert!(matches!(
+            parse_numero_tva("FR83404833049"),
+            Err(IdentifierError::LuhnChecksum { kind: "SIREN", .. })
+        ));
+    }
+}
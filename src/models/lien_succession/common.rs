@@ -1,7 +1,8 @@
 use super::super::schema::lien_succession;
 use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
-use serde::Serialize;
+use diesel::sql_types::{Bool, Date, Integer, Nullable, Timestamp, VarChar};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 #[derive(Insertable, Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
@@ -14,3 +15,32 @@ pub struct LienSuccession {
     pub continuite_economique: bool,
     pub date_dernier_traitement_lien_succession: Option<NaiveDateTime>,
 }
+
+/// Side of the succession chain followed from an establishment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SuccessionDirection {
+    Predecessors,
+    Successors,
+    Both,
+}
+
+/// Link of a succession chain, at `depth` links from the establishment the
+/// chain is followed from, negative on the predecessors side
+#[derive(Debug, QueryableByName, Serialize, ToSchema)]
+pub struct LienSuccessionChainLink {
+    #[diesel(sql_type = VarChar)]
+    pub siret_etablissement_predecesseur: String,
+    #[diesel(sql_type = VarChar)]
+    pub siret_etablissement_successeur: String,
+    #[diesel(sql_type = Date)]
+    pub date_lien_succession: NaiveDate,
+    #[diesel(sql_type = Bool)]
+    pub transfert_siege: bool,
+    #[diesel(sql_type = Bool)]
+    pub continuite_economique: bool,
+    #[diesel(sql_type = Nullable<Timestamp>)]
+    pub date_dernier_traitement_lien_succession: Option<NaiveDateTime>,
+    #[diesel(sql_type = Integer)]
+    pub depth: i32,
+}
@@ -6,12 +6,15 @@ use super::schema::lien_succession::dsl;
 use crate::connectors::{Connectors, local::Connection};
 use crate::update::utils::remote_file::RemoteFile;
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
-use common::LienSuccession;
+use chrono::{NaiveDate, NaiveDateTime};
+use common::{LienSuccession, LienSuccessionChainLink, SuccessionDirection};
+use diesel::pg::upsert::excluded;
 use diesel::pg::{CopyFormat, CopyHeader};
 use diesel::prelude::*;
 use diesel::sql_query;
+use diesel::sql_types::{Bool, Integer, Text};
 use error::Error;
+use std::collections::HashMap;
 
 pub fn get(connection: &mut Connection, siret: &str) -> Result<Vec<LienSuccession>, Error> {
     dsl::lien_succession
@@ -25,6 +28,125 @@ pub fn get(connection: &mut Connection, siret: &str) -> Result<Vec<LienSuccessio
         .map_err(|error| error.into())
 }
 
+/// Links of every establishment of a legal unit, SIRETs starting with the SIREN
+pub fn get_with_siren(
+    connection: &mut Connection,
+    siren: &str,
+) -> Result<Vec<LienSuccession>, Error> {
+    let first_siret = format!("{siren}00000");
+    let last_siret = format!("{siren}99999");
+
+    dsl::lien_succession
+        .select(LienSuccession::as_select())
+        .filter(
+            dsl::siret_etablissement_predecesseur
+                .between(&first_siret, &last_siret)
+                .or(dsl::siret_etablissement_successeur.between(&first_siret, &last_siret)),
+        )
+        .load::<LienSuccession>(connection)
+        .map_err(|error| error.into())
+}
+
+// Walks the links in one direction, `{enabled}` being the bound parameter
+// telling whether it is followed. Establishments already on the path are
+// skipped so cycles end.
+const SUCCESSION_WALK: &str = r#"
+    SELECT
+        l.siret_etablissement_predecesseur,
+        l.siret_etablissement_successeur,
+        l.date_lien_succession,
+        l.transfert_siege,
+        l.continuite_economique,
+        l.date_dernier_traitement_lien_succession,
+        1 AS depth,
+        ARRAY[l.{from}, l.{to}]::text[] AS path
+    FROM lien_succession l
+    WHERE {enabled} AND l.{from} = $1 AND l.{to} <> $1
+    UNION ALL
+    SELECT
+        l.siret_etablissement_predecesseur,
+        l.siret_etablissement_successeur,
+        l.date_lien_succession,
+        l.transfert_siege,
+        l.continuite_economique,
+        l.date_dernier_traitement_lien_succession,
+        w.depth + 1,
+        w.path || l.{to}::text
+    FROM {walk} w
+    JOIN lien_succession l ON l.{from} = w.{to}
+    WHERE w.depth < $2 AND NOT l.{to} = ANY(w.path)
+"#;
+
+/// Links reachable from `siret` within `depth` links, following the
+/// successors, the predecessors or both, ordered by date.
+pub fn get_chain(
+    connection: &mut Connection,
+    siret: &str,
+    direction: SuccessionDirection,
+    depth: i32,
+) -> Result<Vec<LienSuccessionChainLink>, Error> {
+    let walk = |walk: &str, enabled: &str, from: &str, to: &str| {
+        SUCCESSION_WALK
+            .replace("{walk}", walk)
+            .replace("{enabled}", enabled)
+            .replace("{from}", from)
+            .replace("{to}", to)
+    };
+
+    let sql = format!(
+        r#"
+        WITH RECURSIVE
+        successeurs AS ({successeurs}),
+        predecesseurs AS ({predecesseurs})
+        SELECT * FROM (
+            SELECT DISTINCT ON (
+                siret_etablissement_predecesseur,
+                siret_etablissement_successeur,
+                date_lien_succession
+            )
+                siret_etablissement_predecesseur,
+                siret_etablissement_successeur,
+                date_lien_succession,
+                transfert_siege,
+                continuite_economique,
+                date_dernier_traitement_lien_succession,
+                distance AS depth
+            FROM (
+                SELECT s.*, s.depth AS distance FROM successeurs s
+                UNION ALL
+                SELECT p.*, -p.depth AS distance FROM predecesseurs p
+            ) chain
+            ORDER BY
+                siret_etablissement_predecesseur,
+                siret_etablissement_successeur,
+                date_lien_succession,
+                chain.depth
+        ) links
+        ORDER BY date_lien_succession, depth
+        "#,
+        successeurs = walk(
+            "successeurs",
+            "$3",
+            "siret_etablissement_predecesseur",
+            "siret_etablissement_successeur"
+        ),
+        predecesseurs = walk(
+            "predecesseurs",
+            "$4",
+            "siret_etablissement_successeur",
+            "siret_etablissement_predecesseur"
+        ),
+    );
+
+    sql_query(sql)
+        .bind::<Text, _>(siret)
+        .bind::<Integer, _>(depth)
+        .bind::<Bool, _>(direction != SuccessionDirection::Predecessors)
+        .bind::<Bool, _>(direction != SuccessionDirection::Successors)
+        .load::<LienSuccessionChainLink>(connection)
+        .map_err(|error| error.into())
+}
+
 pub struct LienSuccessionModel {}
 
 #[async_trait]
@@ -52,29 +174,97 @@ impl UpdatableModel for LienSuccessionModel {
         connectors: &Connectors,
         remote_file: RemoteFile,
     ) -> Result<bool, UpdatableError> {
-        use super::schema::lien_succession_staging::dsl;
+        // The stock file may hold several copies of a link, they are copied in
+        // an unindexed table first so that the natural key index of the
+        // staging table only sees the most recently processed one
+        diesel::table! {
+            lien_succession_import (
+                siret_etablissement_predecesseur,
+                siret_etablissement_successeur,
+                date_lien_succession
+            ) {
+                siret_etablissement_predecesseur -> Varchar,
+                siret_etablissement_successeur -> Varchar,
+                date_lien_succession -> Date,
+                transfert_siege -> Bool,
+                continuite_economique -> Bool,
+                date_dernier_traitement_lien_succession -> Nullable<Timestamp>,
+            }
+        }
 
         let mut connection = connectors.local.pool.get()?;
 
-        sql_query("TRUNCATE lien_succession_staging").execute(&mut connection)?;
-
-        diesel::copy_from(dsl::lien_succession_staging)
-            .from_raw_data(
-                (
-                    dsl::siret_etablissement_predecesseur,
-                    dsl::siret_etablissement_successeur,
-                    dsl::date_lien_succession,
-                    dsl::transfert_siege,
-                    dsl::continuite_economique,
-                    dsl::date_dernier_traitement_lien_succession,
-                ),
-                |write| copy_remote_zipped_csv(remote_file.to_reader(), write),
-            )
-            .with_delimiter(',')
-            .with_format(CopyFormat::Csv)
-            .with_header(CopyHeader::Set(true))
-            .execute(&mut connection)
-            .map(|count| count > 0)
+        connection
+            .build_transaction()
+            .read_write()
+            .run(|conn| {
+                sql_query("TRUNCATE lien_succession_staging").execute(conn)?;
+                sql_query(
+                    r#"
+                    CREATE TEMPORARY TABLE lien_succession_import ON COMMIT DROP AS
+                    SELECT
+                        siret_etablissement_predecesseur,
+                        siret_etablissement_successeur,
+                        date_lien_succession,
+                        transfert_siege,
+                        continuite_economique,
+                        date_dernier_traitement_lien_succession
+                    FROM lien_succession_staging
+                    WITH NO DATA
+                    "#,
+                )
+                .execute(conn)?;
+
+                let copied_count = diesel::copy_from(lien_succession_import::table)
+                    .from_raw_data(
+                        (
+                            lien_succession_import::siret_etablissement_predecesseur,
+                            lien_succession_import::siret_etablissement_successeur,
+                            lien_succession_import::date_lien_succession,
+                            lien_succession_import::transfert_siege,
+                            lien_succession_import::continuite_economique,
+                            lien_succession_import::date_dernier_traitement_lien_succession,
+                        ),
+                        |write| copy_remote_zipped_csv(remote_file.to_reader(), write),
+                    )
+                    .with_delimiter(',')
+                    .with_format(CopyFormat::Csv)
+                    .with_header(CopyHeader::Set(true))
+                    .execute(conn)?;
+
+                sql_query(
+                    r#"
+                    INSERT INTO lien_succession_staging (
+                        siret_etablissement_predecesseur,
+                        siret_etablissement_successeur,
+                        date_lien_succession,
+                        transfert_siege,
+                        continuite_economique,
+                        date_dernier_traitement_lien_succession
+                    )
+                    SELECT DISTINCT ON (
+                        siret_etablissement_predecesseur,
+                        siret_etablissement_successeur,
+                        date_lien_succession
+                    )
+                        siret_etablissement_predecesseur,
+                        siret_etablissement_successeur,
+                        date_lien_succession,
+                        transfert_siege,
+                        continuite_economique,
+                        date_dernier_traitement_lien_succession
+                    FROM lien_succession_import
+                    ORDER BY
+                        siret_etablissement_predecesseur,
+                        siret_etablissement_successeur,
+                        date_lien_succession,
+                        date_dernier_traitement_lien_succession DESC NULLS LAST
+                    "#,
+                )
+                .execute(conn)?;
+
+                diesel::QueryResult::Ok(copied_count > 0)
+            })
             .map_err(|error| error.into())
     }
 
@@ -151,10 +341,43 @@ impl UpdatableModel for LienSuccessionModel {
             .get_daily_liens_succession(start_timestamp, cursor)
             .await?;
 
+        // A link can only be upserted once by statement, the most recently
+        // processed copy is kept
+        let mut unique_liens: HashMap<(String, String, NaiveDate), LienSuccession> = HashMap::new();
+        for lien in liens_succession {
+            let key = (
+                lien.siret_etablissement_predecesseur.clone(),
+                lien.siret_etablissement_successeur.clone(),
+                lien.date_lien_succession,
+            );
+
+            match unique_liens.get(&key) {
+                Some(existing)
+                    if existing.date_dernier_traitement_lien_succession
+                        > lien.date_dernier_traitement_lien_succession => {}
+                _ => {
+                    unique_liens.insert(key, lien);
+                }
+            }
+        }
+        let liens_succession: Vec<LienSuccession> = unique_liens.into_values().collect();
+
         let mut connection = connectors.local.pool.get()?;
 
         let updated_count = diesel::insert_into(dsl::lien_succession)
             .values(&liens_succession)
+            .on_conflict((
+                dsl::siret_etablissement_predecesseur,
+                dsl::siret_etablissement_successeur,
+                dsl::date_lien_succession,
+            ))
+            .do_update()
+            .set((
+                dsl::transfert_siege.eq(excluded(dsl::transfert_siege)),
+                dsl::continuite_economique.eq(excluded(dsl::continuite_economique)),
+                dsl::date_dernier_traitement_lien_succession
+                    .eq(excluded(dsl::date_dernier_traitement_lien_succession)),
+            ))
             .execute(&mut connection)?;
 
         Ok((next_cursor, updated_count))
@@ -1,7 +1,17 @@
+pub mod api_client;
+pub mod cog;
 pub mod common;
+pub mod diffusion;
 pub mod etablissement;
+pub mod etablissement_periode;
 pub mod group_metadata;
+pub mod identifier;
 pub mod lien_succession;
+pub mod naf_correspondance;
+pub mod nomenclature;
 pub mod schema;
+pub mod search_cursor;
+pub mod search_facet;
 pub mod unite_legale;
+pub mod unite_legale_periode;
 pub mod update_metadata;
@@ -0,0 +1,138 @@
+use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::schema::naf_correspondance::dsl;
+use crate::connectors::Connectors;
+use crate::update::utils::remote_file::RemoteFile;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::pg::{CopyFormat, CopyHeader};
+use diesel::prelude::*;
+use diesel::sql_query;
+
+/// Nomenclature of the `nomenclature_activite_principale` column whose codes
+/// are listed in the correspondence table
+pub const NAF_REV2: &str = "NAFRev2";
+
+/// Match the NAF rev. 2 filter `$param_index` on `alias`, directly or through
+/// the NAF 2025 code of entities not coded in NAF rev. 2
+pub fn naf_rev2_condition(alias: &str, param_index: u32) -> String {
+    format!(
+        "({alias}.activite_principale = ${param_index} \
+        OR ({alias}.nomenclature_activite_principale IS DISTINCT FROM '{NAF_REV2}' \
+        AND {alias}.activite_principale_naf25 IN \
+        (SELECT naf25 FROM naf_correspondance WHERE naf_rev2 = ${param_index})))"
+    )
+}
+
+/// Match the NAF 2025 filter `$param_index` on `alias`, directly or through
+/// the NAF rev. 2 code of entities not coded in NAF 2025 yet
+pub fn naf25_condition(alias: &str, param_index: u32) -> String {
+    format!(
+        "({alias}.activite_principale_naf25 = ${param_index} \
+        OR ({alias}.activite_principale_naf25 IS NULL \
+        AND {alias}.nomenclature_activite_principale = '{NAF_REV2}' \
+        AND {alias}.activite_principale IN \
+        (SELECT naf_rev2 FROM naf_correspondance WHERE naf25 = ${param_index})))"
+    )
+}
+
+pub struct NafCorrespondanceModel {}
+
+#[async_trait]
+impl UpdatableModel for NafCorrespondanceModel {
+    fn count(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        dsl::naf_correspondance
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn count_staging(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        use super::schema::naf_correspondance_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+        dsl::naf_correspondance_staging
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn insert_remote_file_in_staging(
+        &self,
+        connectors: &Connectors,
+        remote_file: RemoteFile,
+    ) -> Result<bool, UpdatableError> {
+        use super::schema::naf_correspondance_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+
+        sql_query("TRUNCATE naf_correspondance_staging").execute(&mut connection)?;
+
+        diesel::copy_from(dsl::naf_correspondance_staging)
+            .from_raw_data((dsl::naf_rev2, dsl::naf25), |write| {
+                copy_remote_zipped_csv(remote_file.to_reader(), write)
+            })
+            .with_delimiter(',')
+            .with_format(CopyFormat::Csv)
+            .with_header(CopyHeader::Set(true))
+            .execute(&mut connection)
+            .map(|count| count > 0)
+            .map_err(|error| error.into())
+    }
+
+    fn swap(&self, connectors: &Connectors) -> Result<(), UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        connection.build_transaction().read_write().run(|conn| {
+            sql_query("ALTER TABLE naf_correspondance RENAME TO naf_correspondance_temp")
+                .execute(conn)?;
+            sql_query("ALTER TABLE naf_correspondance_staging RENAME TO naf_correspondance")
+                .execute(conn)?;
+            sql_query("ALTER TABLE naf_correspondance_temp RENAME TO naf_correspondance_staging")
+                .execute(conn)?;
+            sql_query("TRUNCATE naf_correspondance_staging").execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET last_imported_timestamp = staging_imported_timestamp
+                WHERE group_type = 'naf_correspondances'
+                "#,
+            )
+            .execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET staging_imported_timestamp = NULL
+                WHERE group_type = 'naf_correspondances'
+                "#,
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    // The correspondence table is not published by the INSEE API
+    async fn get_total_count(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+    ) -> Result<u32, UpdatableError> {
+        Ok(0)
+    }
+
+    fn get_last_insee_synced_timestamp(
+        &self,
+        _connectors: &Connectors,
+    ) -> Result<Option<NaiveDateTime>, UpdatableError> {
+        Ok(None)
+    }
+
+    async fn update_daily_data(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+        _cursor: String,
+    ) -> Result<(Option<String>, usize), UpdatableError> {
+        Ok((None, 0))
+    }
+}
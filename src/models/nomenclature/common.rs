@@ -0,0 +1,130 @@
+use super::super::etablissement::common::Etablissement;
+use super::super::naf_correspondance::NAF_REV2;
+use super::super::schema::nomenclature;
+use super::super::unite_legale::common::UniteLegale;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// Reference tables whose codes are used by legal units and establishments
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NomenclatureName {
+    NafRev2,
+    Naf25,
+    CategoriesJuridiques,
+    TranchesEffectifs,
+    TypesVoie,
+}
+
+impl NomenclatureName {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NomenclatureName::NafRev2 => "naf_rev2",
+            NomenclatureName::Naf25 => "naf25",
+            NomenclatureName::CategoriesJuridiques => "categories_juridiques",
+            NomenclatureName::TranchesEffectifs => "tranches_effectifs",
+            NomenclatureName::TypesVoie => "types_voie",
+        }
+    }
+}
+
+/// Entry of a nomenclature, `parent` being the code of the upper level
+#[derive(Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
+#[diesel(table_name = nomenclature)]
+pub struct Poste {
+    pub code: String,
+    pub libelle: String,
+    pub niveau: Option<i16>,
+    pub parent: Option<String>,
+}
+
+/// Labels of the coded fields of an entity, keyed by `<field>_libelle`
+pub type Libelles = BTreeMap<String, Option<String>>;
+
+/// Entity whose fields hold codes of a nomenclature
+pub trait Coded {
+    /// Each coded field with the nomenclature of its value, `None` when the
+    /// field is empty or coded in a nomenclature which is not loaded
+    fn codes(&self) -> Vec<(&'static str, Option<(NomenclatureName, &str)>)>;
+}
+
+/// Activities are only labelled in NAF rev. 2, older nomenclatures are not loaded
+fn activite_principale<'a>(
+    activite_principale: &'a Option<String>,
+    nomenclature_activite_principale: &Option<String>,
+) -> Option<(NomenclatureName, &'a str)> {
+    match nomenclature_activite_principale.as_deref() {
+        Some(NAF_REV2) => activite_principale
+            .as_deref()
+            .map(|code| (NomenclatureName::NafRev2, code)),
+        _ => None,
+    }
+}
+
+fn coded(
+    nomenclature: NomenclatureName,
+    value: &Option<String>,
+) -> Option<(NomenclatureName, &str)> {
+    value.as_deref().map(|code| (nomenclature, code))
+}
+
+impl Coded for UniteLegale {
+    fn codes(&self) -> Vec<(&'static str, Option<(NomenclatureName, &str)>)> {
+        vec![
+            (
+                "activite_principale",
+                activite_principale(
+                    &self.activite_principale,
+                    &self.nomenclature_activite_principale,
+                ),
+            ),
+            (
+                "activite_principale_naf25",
+                coded(NomenclatureName::Naf25, &self.activite_principale_naf25),
+            ),
+            (
+                "categorie_juridique",
+                coded(
+                    NomenclatureName::CategoriesJuridiques,
+                    &self.categorie_juridique,
+                ),
+            ),
+            (
+                "tranche_effectifs",
+                coded(NomenclatureName::TranchesEffectifs, &self.tranche_effectifs),
+            ),
+        ]
+    }
+}
+
+impl Coded for Etablissement {
+    fn codes(&self) -> Vec<(&'static str, Option<(NomenclatureName, &str)>)> {
+        vec![
+            (
+                "activite_principale",
+                activite_principale(
+                    &self.activite_principale,
+                    &self.nomenclature_activite_principale,
+                ),
+            ),
+            (
+                "activite_principale_naf25",
+                coded(NomenclatureName::Naf25, &self.activite_principale_naf25),
+            ),
+            (
+                "tranche_effectifs",
+                coded(NomenclatureName::TranchesEffectifs, &self.tranche_effectifs),
+            ),
+            (
+                "type_voie",
+                coded(NomenclatureName::TypesVoie, &self.type_voie),
+            ),
+            (
+                "type_voie_2",
+                coded(NomenclatureName::TypesVoie, &self.type_voie_2),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,7 @@
+use custom_error::custom_error;
+
+custom_error! { pub Error
+    UnknownNomenclature{name: String} = "Unknown nomenclature {name}.",
+    LocalConnectionFailed{source: r2d2::Error} = "Unable to connect to local database ({source}).",
+    Database{source: diesel::result::Error} = "Unable to run some operations on nomenclature ({source}).",
+}
@@ -0,0 +1,212 @@
+pub mod common;
+pub mod error;
+
+use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::schema::nomenclature::dsl;
+use crate::connectors::{Connectors, local::Connection};
+use crate::update::utils::remote_file::RemoteFile;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use common::{Coded, Libelles, NomenclatureName, Poste};
+use diesel::pg::{CopyFormat, CopyHeader};
+use diesel::prelude::*;
+use diesel::sql_query;
+use error::Error;
+use std::collections::{HashMap, HashSet};
+
+pub fn parse_name(name: &str) -> Result<NomenclatureName, Error> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).map_err(|_| {
+        Error::UnknownNomenclature {
+            name: name.to_string(),
+        }
+    })
+}
+
+/// Entries of a nomenclature ordered by code, optionally restricted to a level
+pub fn get(
+    connection: &mut Connection,
+    name: NomenclatureName,
+    niveau: Option<i16>,
+) -> Result<Vec<Poste>, Error> {
+    let mut query = dsl::nomenclature
+        .select(Poste::as_select())
+        .filter(dsl::nom.eq(name.as_str()))
+        .order(dsl::code)
+        .into_boxed();
+
+    if let Some(niveau) = niveau {
+        query = query.filter(dsl::niveau.eq(niveau));
+    }
+
+    query
+        .load::<Poste>(connection)
+        .map_err(|error| error.into())
+}
+
+/// Labels of the codes used by a set of entities, loaded with a single query
+pub struct LibelleIndex {
+    libelles: HashMap<(String, String), String>,
+}
+
+impl LibelleIndex {
+    pub fn load<'a>(
+        connection: &mut Connection,
+        entities: impl IntoIterator<Item = &'a dyn Coded>,
+    ) -> Result<Self, Error> {
+        let mut names = HashSet::new();
+        let mut codes = HashSet::new();
+
+        for entity in entities {
+            for (name, code) in entity.codes().into_iter().filter_map(|(_, coded)| coded) {
+                names.insert(name.as_str());
+                codes.insert(code.to_string());
+            }
+        }
+
+        if codes.is_empty() {
+            return Ok(LibelleIndex {
+                libelles: HashMap::new(),
+            });
+        }
+
+        let libelles = dsl::nomenclature
+            .select((dsl::nom, dsl::code, dsl::libelle))
+            .filter(dsl::nom.eq_any(names))
+            .filter(dsl::code.eq_any(codes))
+            .load::<(String, String, String)>(connection)?
+            .into_iter()
+            .map(|(name, code, libelle)| ((name, code), libelle))
+            .collect();
+
+        Ok(LibelleIndex { libelles })
+    }
+
+    /// Labels of `entity`, codes missing from the nomenclature being `null`
+    pub fn libelles<C: Coded>(&self, entity: &C) -> Libelles {
+        entity
+            .codes()
+            .into_iter()
+            .map(|(field, coded)| {
+                let libelle = coded.and_then(|(name, code)| {
+                    self.libelles
+                        .get(&(name.as_str().to_string(), code.to_string()))
+                        .cloned()
+                });
+
+                (format!("{field}_libelle"), libelle)
+            })
+            .collect()
+    }
+}
+
+pub struct NomenclatureModel {}
+
+#[async_trait]
+impl UpdatableModel for NomenclatureModel {
+    fn count(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        dsl::nomenclature
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn count_staging(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        use super::schema::nomenclature_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+        dsl::nomenclature_staging
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn insert_remote_file_in_staging(
+        &self,
+        connectors: &Connectors,
+        remote_file: RemoteFile,
+    ) -> Result<bool, UpdatableError> {
+        use super::schema::nomenclature_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+
+        sql_query("TRUNCATE nomenclature_staging").execute(&mut connection)?;
+
+        diesel::copy_from(dsl::nomenclature_staging)
+            .from_raw_data(
+                (dsl::nom, dsl::code, dsl::libelle, dsl::niveau, dsl::parent),
+                |write| copy_remote_zipped_csv(remote_file.to_reader(), write),
+            )
+            .with_delimiter(',')
+            .with_format(CopyFormat::Csv)
+            .with_header(CopyHeader::Set(true))
+            .execute(&mut connection)
+            .map(|count| count > 0)
+            .map_err(|error| error.into())
+    }
+
+    fn swap(&self, connectors: &Connectors) -> Result<(), UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        connection.build_transaction().read_write().run(|conn| {
+            // Nomenclatures missing from the imported file, like the ones
+            // seeded by the migrations, are kept
+            sql_query(
+                r#"
+                INSERT INTO nomenclature_staging (nom, code, libelle, niveau, parent)
+                SELECT nom, code, libelle, niveau, parent
+                FROM nomenclature
+                WHERE nom NOT IN (SELECT DISTINCT nom FROM nomenclature_staging)
+                "#,
+            )
+            .execute(conn)?;
+            sql_query("ALTER TABLE nomenclature RENAME TO nomenclature_temp").execute(conn)?;
+            sql_query("ALTER TABLE nomenclature_staging RENAME TO nomenclature").execute(conn)?;
+            sql_query("ALTER TABLE nomenclature_temp RENAME TO nomenclature_staging")
+                .execute(conn)?;
+            sql_query("TRUNCATE nomenclature_staging").execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET last_imported_timestamp = staging_imported_timestamp
+                WHERE group_type = 'nomenclatures'
+                "#,
+            )
+            .execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET staging_imported_timestamp = NULL
+                WHERE group_type = 'nomenclatures'
+                "#,
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    // Nomenclatures are not published by the INSEE API
+    async fn get_total_count(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+    ) -> Result<u32, UpdatableError> {
+        Ok(0)
+    }
+
+    fn get_last_insee_synced_timestamp(
+        &self,
+        _connectors: &Connectors,
+    ) -> Result<Option<NaiveDateTime>, UpdatableError> {
+        Ok(None)
+    }
+
+    async fn update_daily_data(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+        _cursor: String,
+    ) -> Result<(Option<String>, usize), UpdatableError> {
+        Ok((None, 0))
+    }
+}
@@ -1,5 +1,294 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    api_client (id) {
+        id -> Int4,
+        name -> Text,
+        key_prefix -> Text,
+        key_hash -> Text,
+        scopes -> Array<Text>,
+        expires_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_arrondissement (id) {
+        id -> Uuid,
+        #[max_length = 4]
+        code_arrondissement -> Varchar,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_arrondissement_staging (id) {
+        id -> Uuid,
+        #[max_length = 4]
+        code_arrondissement -> Varchar,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_commune (id) {
+        id -> Uuid,
+        #[max_length = 4]
+        type_commune -> Varchar,
+        #[max_length = 5]
+        code_commune -> Varchar,
+        #[max_length = 2]
+        code_region -> Nullable<Varchar>,
+        #[max_length = 3]
+        code_departement -> Nullable<Varchar>,
+        #[max_length = 4]
+        code_collectivite -> Nullable<Varchar>,
+        #[max_length = 4]
+        code_arrondissement -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+        #[max_length = 5]
+        code_canton -> Nullable<Varchar>,
+        #[max_length = 5]
+        code_commune_parent -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_commune_courante (code_commune) {
+        #[max_length = 5]
+        code_commune -> Varchar,
+        #[max_length = 5]
+        code_commune_courante -> Varchar,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_commune_courante_staging (code_commune) {
+        #[max_length = 5]
+        code_commune -> Varchar,
+        #[max_length = 5]
+        code_commune_courante -> Varchar,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_commune_staging (id) {
+        id -> Uuid,
+        #[max_length = 4]
+        type_commune -> Varchar,
+        #[max_length = 5]
+        code_commune -> Varchar,
+        #[max_length = 2]
+        code_region -> Nullable<Varchar>,
+        #[max_length = 3]
+        code_departement -> Nullable<Varchar>,
+        #[max_length = 4]
+        code_collectivite -> Nullable<Varchar>,
+        #[max_length = 4]
+        code_arrondissement -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+        #[max_length = 5]
+        code_canton -> Nullable<Varchar>,
+        #[max_length = 5]
+        code_commune_parent -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_departement (id) {
+        id -> Uuid,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_departement_staging (id) {
+        id -> Uuid,
+        #[max_length = 3]
+        code_departement -> Varchar,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_mouvement_commune (id) {
+        id -> Uuid,
+        #[max_length = 2]
+        type_evenement -> Varchar,
+        date_effet -> Date,
+        #[max_length = 4]
+        type_commune_avant -> Varchar,
+        #[max_length = 5]
+        code_commune_avant -> Varchar,
+        #[max_length = 1]
+        tncc_avant -> Nullable<Varchar>,
+        ncc_avant -> Nullable<Text>,
+        nccenr_avant -> Nullable<Text>,
+        libelle_avant -> Nullable<Text>,
+        #[max_length = 4]
+        type_commune_apres -> Varchar,
+        #[max_length = 5]
+        code_commune_apres -> Varchar,
+        #[max_length = 1]
+        tncc_apres -> Nullable<Varchar>,
+        ncc_apres -> Nullable<Text>,
+        nccenr_apres -> Nullable<Text>,
+        libelle_apres -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_mouvement_commune_staging (id) {
+        id -> Uuid,
+        #[max_length = 2]
+        type_evenement -> Varchar,
+        date_effet -> Date,
+        #[max_length = 4]
+        type_commune_avant -> Varchar,
+        #[max_length = 5]
+        code_commune_avant -> Varchar,
+        #[max_length = 1]
+        tncc_avant -> Nullable<Varchar>,
+        ncc_avant -> Nullable<Text>,
+        nccenr_avant -> Nullable<Text>,
+        libelle_avant -> Nullable<Text>,
+        #[max_length = 4]
+        type_commune_apres -> Varchar,
+        #[max_length = 5]
+        code_commune_apres -> Varchar,
+        #[max_length = 1]
+        tncc_apres -> Nullable<Varchar>,
+        ncc_apres -> Nullable<Text>,
+        nccenr_apres -> Nullable<Text>,
+        libelle_apres -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_region (id) {
+        id -> Uuid,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    cog_region_staging (id) {
+        id -> Uuid,
+        #[max_length = 2]
+        code_region -> Varchar,
+        #[max_length = 5]
+        cheflieu -> Nullable<Varchar>,
+        #[max_length = 1]
+        tncc -> Nullable<Varchar>,
+        ncc -> Nullable<Text>,
+        nccenr -> Nullable<Text>,
+        libelle -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
@@ -69,6 +358,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    etablissement_periode (id) {
+        id -> Uuid,
+        #[max_length = 9]
+        siren -> Varchar,
+        nic -> Text,
+        #[max_length = 14]
+        siret -> Varchar,
+        date_fin -> Nullable<Date>,
+        date_debut -> Nullable<Date>,
+        #[max_length = 1]
+        etat_administratif -> Varchar,
+        changement_etat_administratif -> Bool,
+        enseigne_1 -> Nullable<Text>,
+        enseigne_2 -> Nullable<Text>,
+        enseigne_3 -> Nullable<Text>,
+        changement_enseigne -> Bool,
+        denomination_usuelle -> Nullable<Text>,
+        changement_denomination_usuelle -> Bool,
+        activite_principale -> Nullable<Text>,
+        nomenclature_activite_principale -> Nullable<Text>,
+        changement_activite_principale -> Bool,
+        caractere_employeur -> Nullable<Text>,
+        changement_caractere_employeur -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    etablissement_periode_staging (id) {
+        id -> Uuid,
+        #[max_length = 9]
+        siren -> Varchar,
+        nic -> Text,
+        #[max_length = 14]
+        siret -> Varchar,
+        date_fin -> Nullable<Date>,
+        date_debut -> Nullable<Date>,
+        #[max_length = 1]
+        etat_administratif -> Varchar,
+        changement_etat_administratif -> Bool,
+        enseigne_1 -> Nullable<Text>,
+        enseigne_2 -> Nullable<Text>,
+        enseigne_3 -> Nullable<Text>,
+        changement_enseigne -> Bool,
+        denomination_usuelle -> Nullable<Text>,
+        changement_denomination_usuelle -> Bool,
+        activite_principale -> Nullable<Text>,
+        nomenclature_activite_principale -> Nullable<Text>,
+        changement_activite_principale -> Bool,
+        caractere_employeur -> Nullable<Text>,
+        changement_caractere_employeur -> Bool,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
@@ -192,6 +541,66 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    naf_correspondance (id) {
+        id -> Uuid,
+        #[max_length = 6]
+        naf_rev2 -> Varchar,
+        #[max_length = 6]
+        naf25 -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    naf_correspondance_staging (id) {
+        id -> Uuid,
+        #[max_length = 6]
+        naf_rev2 -> Varchar,
+        #[max_length = 6]
+        naf25 -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    nomenclature (id) {
+        id -> Uuid,
+        #[max_length = 32]
+        nom -> Varchar,
+        #[max_length = 8]
+        code -> Varchar,
+        libelle -> Text,
+        niveau -> Nullable<Int2>,
+        #[max_length = 8]
+        parent -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    nomenclature_staging (id) {
+        id -> Uuid,
+        #[max_length = 32]
+        nom -> Varchar,
+        #[max_length = 8]
+        code -> Varchar,
+        libelle -> Text,
+        niveau -> Nullable<Int2>,
+        #[max_length = 8]
+        parent -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use postgis_diesel::sql_types::*;
@@ -240,6 +649,90 @@ diesel::table! {
         caractere_employeur -> Nullable<Varchar>,
         activite_principale_naf25 -> Nullable<Text>,
         search_denomination -> Nullable<Text>,
+        suggest_denomination -> Nullable<Text>,
+        numero_tva_intracommunautaire -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    unite_legale_periode (id) {
+        id -> Uuid,
+        #[max_length = 9]
+        siren -> Varchar,
+        date_fin -> Nullable<Date>,
+        date_debut -> Nullable<Date>,
+        #[max_length = 1]
+        etat_administratif -> Varchar,
+        changement_etat_administratif -> Bool,
+        nom -> Nullable<Text>,
+        changement_nom -> Bool,
+        nom_usage -> Nullable<Text>,
+        changement_nom_usage -> Bool,
+        denomination -> Nullable<Text>,
+        changement_denomination -> Bool,
+        denomination_usuelle_1 -> Nullable<Text>,
+        denomination_usuelle_2 -> Nullable<Text>,
+        denomination_usuelle_3 -> Nullable<Text>,
+        changement_denomination_usuelle -> Bool,
+        categorie_juridique -> Nullable<Text>,
+        changement_categorie_juridique -> Bool,
+        activite_principale -> Nullable<Text>,
+        nomenclature_activite_principale -> Nullable<Text>,
+        changement_activite_principale -> Bool,
+        nic_siege -> Nullable<Text>,
+        changement_nic_siege -> Bool,
+        economie_sociale_solidaire -> Nullable<Text>,
+        changement_economie_sociale_solidaire -> Bool,
+        #[max_length = 1]
+        societe_mission -> Nullable<Varchar>,
+        changement_societe_mission -> Bool,
+        #[max_length = 1]
+        caractere_employeur -> Nullable<Varchar>,
+        changement_caractere_employeur -> Bool,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use postgis_diesel::sql_types::*;
+
+    unite_legale_periode_staging (id) {
+        id -> Uuid,
+        #[max_length = 9]
+        siren -> Varchar,
+        date_fin -> Nullable<Date>,
+        date_debut -> Nullable<Date>,
+        #[max_length = 1]
+        etat_administratif -> Varchar,
+        changement_etat_administratif -> Bool,
+        nom -> Nullable<Text>,
+        changement_nom -> Bool,
+        nom_usage -> Nullable<Text>,
+        changement_nom_usage -> Bool,
+        denomination -> Nullable<Text>,
+        changement_denomination -> Bool,
+        denomination_usuelle_1 -> Nullable<Text>,
+        denomination_usuelle_2 -> Nullable<Text>,
+        denomination_usuelle_3 -> Nullable<Text>,
+        changement_denomination_usuelle -> Bool,
+        categorie_juridique -> Nullable<Text>,
+        changement_categorie_juridique -> Bool,
+        activite_principale -> Nullable<Text>,
+        nomenclature_activite_principale -> Nullable<Text>,
+        changement_activite_principale -> Bool,
+        nic_siege -> Nullable<Text>,
+        changement_nic_siege -> Bool,
+        economie_sociale_solidaire -> Nullable<Text>,
+        changement_economie_sociale_solidaire -> Bool,
+        #[max_length = 1]
+        societe_mission -> Nullable<Varchar>,
+        changement_societe_mission -> Bool,
+        #[max_length = 1]
+        caractere_employeur -> Nullable<Varchar>,
+        changement_caractere_employeur -> Bool,
     }
 }
 
@@ -291,6 +784,8 @@ diesel::table! {
         caractere_employeur -> Nullable<Varchar>,
         activite_principale_naf25 -> Nullable<Text>,
         search_denomination -> Nullable<Text>,
+        suggest_denomination -> Nullable<Text>,
+        numero_tva_intracommunautaire -> Nullable<Text>,
     }
 }
 
@@ -313,12 +808,33 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_client,
+    cog_arrondissement,
+    cog_arrondissement_staging,
+    cog_commune,
+    cog_commune_courante,
+    cog_commune_courante_staging,
+    cog_commune_staging,
+    cog_departement,
+    cog_departement_staging,
+    cog_mouvement_commune,
+    cog_mouvement_commune_staging,
+    cog_region,
+    cog_region_staging,
     etablissement,
+    etablissement_periode,
+    etablissement_periode_staging,
     etablissement_staging,
     group_metadata,
     lien_succession,
     lien_succession_staging,
+    naf_correspondance,
+    naf_correspondance_staging,
+    nomenclature,
+    nomenclature_staging,
     unite_legale,
+    unite_legale_periode,
+    unite_legale_periode_staging,
     unite_legale_staging,
     update_metadata,
 );
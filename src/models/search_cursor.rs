@@ -0,0 +1,50 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Opaque keyset cursor: the sort it was built for, the value of the sort key
+/// on the last returned row, and the primary key of this row as a tie-breaker.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SearchCursor<S, D> {
+    pub sort: S,
+    pub direction: D,
+    pub value: Option<String>,
+    pub key: String,
+}
+
+impl<S, D> SearchCursor<S, D>
+where
+    S: Serialize + DeserializeOwned,
+    D: Serialize + DeserializeOwned,
+{
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+/// Build the keyset condition following a cursor for a sort key ordered with
+/// NULLS LAST. `cast` is the SQL type used to compare the textual cursor value.
+pub fn keyset_condition(
+    sort_expression: &str,
+    cast: &str,
+    key_column: &str,
+    ascending: bool,
+    has_value: bool,
+    param_index: u32,
+) -> String {
+    let cmp = if ascending { ">" } else { "<" };
+
+    if has_value {
+        format!(
+            "({sort_expression} {cmp} ${param_index}::{cast} OR ({sort_expression} = ${param_index}::{cast} AND {key_column} {cmp} ${}) OR {sort_expression} IS NULL)",
+            param_index + 1
+        )
+    } else {
+        format!("({sort_expression} IS NULL AND {key_column} {cmp} ${param_index})")
+    }
+}
@@ -0,0 +1,106 @@
+use diesel::pg::{Pg, PgConnection};
+use diesel::prelude::*;
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
+use diesel::sql_types::{BigInt, Jsonb, Text};
+use serde::{Serialize, de::DeserializeOwned};
+use utoipa::ToSchema;
+
+pub const MAX_FACET_BUCKETS: usize = 20;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FacetBucket {
+    pub value: String,
+    pub count: i64,
+}
+
+#[derive(QueryableByName)]
+struct FacetCount {
+    #[diesel(sql_type = Text)]
+    value: String,
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct FacetAggregation {
+    #[diesel(sql_type = Jsonb)]
+    agg: serde_json::Value,
+}
+
+/// Parse a comma separated list of facet names, returning the first unknown
+/// name on failure.
+pub fn parse_facets<F>(facets: &str) -> Result<Vec<F>, String>
+where
+    F: DeserializeOwned + PartialEq,
+{
+    let mut parsed = Vec::new();
+
+    for name in facets.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let facet: F = serde_json::from_value(serde_json::Value::String(name.to_string()))
+            .map_err(|_| name.to_string())?;
+
+        if !parsed.contains(&facet) {
+            parsed.push(facet);
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Build the query counting the values of `field` over the filtered set, the
+/// table being aliased as `alias`. Text searches are answered by the bm25
+/// index with pdb.agg, which requires `field` to be indexed as a literal.
+pub fn facet_query(
+    alias: &str,
+    field: &str,
+    from_clause: &str,
+    where_clause: &str,
+    text_search: bool,
+) -> String {
+    if text_search {
+        format!(
+            "SELECT pdb.agg('{{\"terms\": {{\"field\": \"{field}\", \"size\": {MAX_FACET_BUCKETS}}}}}') AS agg FROM {from_clause} {where_clause}"
+        )
+    } else {
+        format!(
+            "SELECT {alias}.{field}::text AS value, COUNT(*) AS count FROM {from_clause} {where_clause} GROUP BY {alias}.{field} HAVING {alias}.{field} IS NOT NULL ORDER BY count DESC, value ASC LIMIT {MAX_FACET_BUCKETS}"
+        )
+    }
+}
+
+pub fn load_facet(
+    connection: &mut PgConnection,
+    query: BoxedSqlQuery<'_, Pg, SqlQuery>,
+    text_search: bool,
+) -> QueryResult<Vec<FacetBucket>> {
+    if !text_search {
+        return Ok(query
+            .load::<FacetCount>(connection)?
+            .into_iter()
+            .map(|c| FacetBucket {
+                value: c.value,
+                count: c.count,
+            })
+            .collect());
+    }
+
+    let aggregation = query.get_result::<FacetAggregation>(connection)?;
+
+    Ok(aggregation.agg["buckets"]
+        .as_array()
+        .map(|buckets| {
+            buckets
+                .iter()
+                .filter_map(|bucket| {
+                    Some(FacetBucket {
+                        value: match &bucket["key"] {
+                            serde_json::Value::String(key) => key.clone(),
+                            key => key.to_string(),
+                        },
+                        count: bucket["doc_count"].as_f64()? as i64,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
@@ -1,8 +1,10 @@
 use super::super::schema::unite_legale;
+use super::super::search_facet::FacetBucket;
 use chrono::{NaiveDate, NaiveDateTime};
 use diesel::prelude::*;
 use diesel::sql_types::{BigInt, Float4, Jsonb, Nullable, Text, VarChar};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::{IntoParams, ToSchema};
 
 #[derive(Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
@@ -43,6 +45,8 @@ pub struct UniteLegale {
     pub societe_mission: Option<String>,
     pub caractere_employeur: Option<String>,
     pub activite_principale_naf25: Option<String>,
+    /// Intra-community VAT number computed from the SIREN
+    pub numero_tva_intracommunautaire: Option<String>,
 }
 
 // Separate insertable struct that excludes GENERATED ALWAYS columns
@@ -86,7 +90,7 @@ pub struct UniteLegaleInsertable {
     pub activite_principale_naf25: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum UniteLegaleSortField {
     DateCreation,
@@ -94,13 +98,36 @@ pub enum UniteLegaleSortField {
     Relevance,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SortDirection {
     Asc,
     Desc,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UniteLegaleFacet {
+    ActivitePrincipale,
+    EtatAdministratif,
+    CategorieJuridique,
+    CategorieEntreprise,
+    TrancheEffectifs,
+}
+
+impl UniteLegaleFacet {
+    /// Name of the facet and of the counted column
+    pub fn field(self) -> &'static str {
+        match self {
+            UniteLegaleFacet::ActivitePrincipale => "activite_principale",
+            UniteLegaleFacet::EtatAdministratif => "etat_administratif",
+            UniteLegaleFacet::CategorieJuridique => "categorie_juridique",
+            UniteLegaleFacet::CategorieEntreprise => "categorie_entreprise",
+            UniteLegaleFacet::TrancheEffectifs => "tranche_effectifs",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
 pub enum EtatAdministratif {
     A,
@@ -112,6 +139,7 @@ pub struct UniteLegaleSearchParams {
     pub q: Option<String>,
     pub etat_administratif: Option<EtatAdministratif>,
     pub activite_principale: Option<String>,
+    pub activite_principale_naf25: Option<String>,
     pub categorie_juridique: Option<String>,
     pub categorie_entreprise: Option<String>,
     pub date_creation: Option<NaiveDate>,
@@ -120,6 +148,8 @@ pub struct UniteLegaleSearchParams {
     pub direction: Option<SortDirection>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub cursor: Option<String>,
+    pub facets: Option<String>,
 }
 
 #[derive(Debug, QueryableByName, Serialize, ToSchema)]
@@ -150,6 +180,8 @@ pub struct UniteLegaleSearchResult {
     pub total: Option<i64>,
     #[diesel(sql_type = Nullable<Jsonb>)]
     pub total_json: Option<serde_json::Value>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub sort_value: Option<String>,
 }
 
 pub struct UniteLegaleSearchOutput {
@@ -158,6 +190,8 @@ pub struct UniteLegaleSearchOutput {
     pub offset: i64,
     pub sort: UniteLegaleSortField,
     pub direction: SortDirection,
+    pub next_cursor: Option<String>,
+    pub facets: Option<BTreeMap<String, Vec<FacetBucket>>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -168,6 +202,9 @@ pub struct UniteLegaleSearchResponse {
     pub offset: i64,
     pub sort: UniteLegaleSortField,
     pub direction: SortDirection,
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<BTreeMap<String, Vec<FacetBucket>>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -185,3 +222,43 @@ pub struct UniteLegaleSearchResultResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score: Option<f32>,
 }
+
+impl From<UniteLegaleSearchResult> for UniteLegaleSearchResultResponse {
+    fn from(r: UniteLegaleSearchResult) -> Self {
+        UniteLegaleSearchResultResponse {
+            siren: r.siren,
+            etat_administratif: r.etat_administratif,
+            date_creation: r.date_creation,
+            denomination: r.denomination,
+            denomination_usuelle_1: r.denomination_usuelle_1,
+            denomination_usuelle_2: r.denomination_usuelle_2,
+            denomination_usuelle_3: r.denomination_usuelle_3,
+            activite_principale: r.activite_principale,
+            categorie_juridique: r.categorie_juridique,
+            categorie_entreprise: r.categorie_entreprise,
+            score: r.score,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UniteLegaleSuggestParams {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, QueryableByName, Serialize, ToSchema)]
+pub struct UniteLegaleSuggestion {
+    #[diesel(sql_type = VarChar)]
+    pub siren: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub siret_siege: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub nom: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub code_postal: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub libelle_commune: Option<String>,
+    #[diesel(sql_type = VarChar)]
+    pub etat_administratif: String,
+}
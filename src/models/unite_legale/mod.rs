@@ -2,21 +2,26 @@ pub mod common;
 pub mod error;
 
 use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::naf_correspondance::{naf_rev2_condition, naf25_condition};
 use super::schema::unite_legale::dsl;
+use super::search_cursor::{SearchCursor, keyset_condition};
+use super::search_facet::{FacetBucket, facet_query, load_facet, parse_facets};
 use crate::connectors::{Connectors, local::Connection};
 use crate::update::utils::remote_file::RemoteFile;
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
 use common::{
-    SortDirection, UniteLegale, UniteLegaleSearchOutput, UniteLegaleSearchParams,
-    UniteLegaleSearchResult, UniteLegaleSortField,
+    SortDirection, UniteLegale, UniteLegaleFacet, UniteLegaleSearchOutput, UniteLegaleSearchParams,
+    UniteLegaleSearchResult, UniteLegaleSortField, UniteLegaleSuggestion,
 };
 use diesel::pg::upsert::excluded;
-use diesel::pg::{CopyFormat, CopyHeader};
+use diesel::pg::{CopyFormat, CopyHeader, Pg};
 use diesel::prelude::*;
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
 use diesel::sql_query;
-use diesel::sql_types::{Date, Text};
+use diesel::sql_types::{BigInt, Date, Text};
 use error::Error;
+use std::collections::BTreeMap;
 
 pub fn get(connection: &mut Connection, siren: &str) -> Result<UniteLegale, Error> {
     dsl::unite_legale
@@ -26,16 +31,65 @@ pub fn get(connection: &mut Connection, siren: &str) -> Result<UniteLegale, Erro
         .map_err(|error| error.into())
 }
 
-pub fn search(
+pub fn get_batch(
     connection: &mut Connection,
-    params: &UniteLegaleSearchParams,
-) -> Result<UniteLegaleSearchOutput, Error> {
-    let has_q = params.q.is_some();
+    sirens: &[String],
+) -> Result<Vec<UniteLegale>, Error> {
+    dsl::unite_legale
+        .filter(dsl::siren.eq_any(sirens))
+        .select(UniteLegale::as_select())
+        .load::<UniteLegale>(connection)
+        .map_err(|error| error.into())
+}
 
-    let limit = params.limit.unwrap_or(20).clamp(1, 100);
-    let offset = params.offset.unwrap_or(0).clamp(0, 10_000);
+type UniteLegaleCursor = SearchCursor<UniteLegaleSortField, SortDirection>;
+
+const EXPORT_CURSOR: &str = "unite_legale_export";
+const EXPORT_BATCH_SIZE: usize = 1000;
+
+fn resolve_sort(params: &UniteLegaleSearchParams) -> (UniteLegaleSortField, SortDirection) {
+    let sort_field = params.sort.unwrap_or(if params.q.is_some() {
+        UniteLegaleSortField::Relevance
+    } else {
+        UniteLegaleSortField::DateCreation
+    });
+
+    (sort_field, params.direction.unwrap_or(SortDirection::Desc))
+}
+
+// Relevance scores cannot be used as a keyset
+fn sort_key(sort_field: UniteLegaleSortField) -> Option<(&'static str, &'static str)> {
+    match sort_field {
+        UniteLegaleSortField::DateCreation => Some(("u.date_creation", "date")),
+        UniteLegaleSortField::DateDebut => Some(("u.date_debut", "date")),
+        UniteLegaleSortField::Relevance => None,
+    }
+}
 
-    // Build SELECT columns
+fn order_by(sort_field: UniteLegaleSortField, direction: SortDirection) -> &'static str {
+    match (sort_field, direction) {
+        (UniteLegaleSortField::Relevance, SortDirection::Asc) => "score ASC",
+        (UniteLegaleSortField::Relevance, SortDirection::Desc) => "score DESC",
+        (UniteLegaleSortField::DateCreation, SortDirection::Asc) => {
+            "u.date_creation ASC NULLS LAST, u.siren ASC"
+        }
+        (UniteLegaleSortField::DateCreation, SortDirection::Desc) => {
+            "u.date_creation DESC NULLS LAST, u.siren DESC"
+        }
+        (UniteLegaleSortField::DateDebut, SortDirection::Asc) => {
+            "u.date_debut ASC NULLS LAST, u.siren ASC"
+        }
+        (UniteLegaleSortField::DateDebut, SortDirection::Desc) => {
+            "u.date_debut DESC NULLS LAST, u.siren DESC"
+        }
+    }
+}
+
+fn select_columns(
+    params: &UniteLegaleSearchParams,
+    with_total: bool,
+    sort_key: Option<(&str, &str)>,
+) -> Vec<String> {
     let mut select_columns = vec![
         "u.siren".to_string(),
         "u.etat_administratif".to_string(),
@@ -49,151 +103,347 @@ pub fn search(
         "u.categorie_entreprise".to_string(),
     ];
 
-    if has_q {
+    if params.q.is_some() {
         select_columns.push("pdb.score(u.siren) AS score".to_string());
-        select_columns.push("NULL::bigint AS total".to_string());
-        select_columns.push(
-            "pdb.agg('{\"value_count\": {\"field\": \"siren\"}}') OVER () AS total_json"
-                .to_string(),
-        );
     } else {
         select_columns.push("NULL::real AS score".to_string());
-        select_columns.push("COUNT(*) OVER() AS total".to_string());
-        select_columns.push("NULL::jsonb AS total_json".to_string());
     }
 
+    match (with_total, params.q.is_some()) {
+        (true, true) => {
+            select_columns.push("NULL::bigint AS total".to_string());
+            select_columns.push(
+                "pdb.agg('{\"value_count\": {\"field\": \"siren\"}}') OVER () AS total_json"
+                    .to_string(),
+            );
+        }
+        (true, false) => {
+            select_columns.push("COUNT(*) OVER() AS total".to_string());
+            select_columns.push("NULL::jsonb AS total_json".to_string());
+        }
+        (false, _) => {
+            select_columns.push("NULL::bigint AS total".to_string());
+            select_columns.push("NULL::jsonb AS total_json".to_string());
+        }
+    }
+
+    match sort_key {
+        Some((sort_expression, _)) => {
+            select_columns.push(format!("{sort_expression}::text AS sort_value"))
+        }
+        None => select_columns.push("NULL::text AS sort_value".to_string()),
+    }
+
+    select_columns
+}
+
+// Placeholders are numbered in the order used by bind_search_filter
+fn build_where_clause(
+    params: &UniteLegaleSearchParams,
+    keyset: Option<(&UniteLegaleCursor, (&str, &str), SortDirection)>,
+) -> String {
     // Build WHERE conditions
     let mut conditions: Vec<String> = Vec::new();
     let mut param_index = 1u32;
 
     // Text search
-    if has_q {
+    if params.q.is_some() {
         conditions.push(format!("u.search_denomination ||| ${param_index}"));
         param_index += 1;
     }
 
     // Field filters
-    let mut field_param_indices: Vec<(String, u32)> = Vec::new();
-
-    if let Some(ref _v) = params.etat_administratif {
+    if params.etat_administratif.is_some() {
         conditions.push(format!("u.etat_administratif = ${param_index}"));
-        field_param_indices.push(("etat_administratif".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.activite_principale {
-        conditions.push(format!("u.activite_principale = ${param_index}"));
-        field_param_indices.push(("activite_principale".to_string(), param_index));
+    if params.activite_principale.is_some() {
+        conditions.push(naf_rev2_condition("u", param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.categorie_juridique {
+    if params.activite_principale_naf25.is_some() {
+        conditions.push(naf25_condition("u", param_index));
+        param_index += 1;
+    }
+    if params.categorie_juridique.is_some() {
         conditions.push(format!("u.categorie_juridique = ${param_index}"));
-        field_param_indices.push(("categorie_juridique".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.categorie_entreprise {
+    if params.categorie_entreprise.is_some() {
         conditions.push(format!("u.categorie_entreprise = ${param_index}"));
-        field_param_indices.push(("categorie_entreprise".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.date_creation {
+    if params.date_creation.is_some() {
         conditions.push(format!("u.date_creation = ${param_index}"));
-        field_param_indices.push(("date_creation".to_string(), param_index));
         param_index += 1;
     }
-    if let Some(ref _v) = params.date_debut {
+    if params.date_debut.is_some() {
         conditions.push(format!("u.date_debut = ${param_index}"));
-        field_param_indices.push(("date_debut".to_string(), param_index));
         param_index += 1;
     }
 
+    // Keyset pagination
+    if let Some((cursor, (sort_expression, cast), direction)) = keyset {
+        conditions.push(keyset_condition(
+            sort_expression,
+            cast,
+            "u.siren",
+            direction == SortDirection::Asc,
+            cursor.value.is_some(),
+            param_index,
+        ));
+        param_index += if cursor.value.is_some() { 2 } else { 1 };
+    }
+
     let _ = param_index; // suppress unused warning
 
-    // Build ORDER BY
-    let sort_field = params.sort.unwrap_or(if has_q {
-        UniteLegaleSortField::Relevance
+    if conditions.is_empty() {
+        String::new()
     } else {
-        UniteLegaleSortField::DateCreation
-    });
+        format!("WHERE {}", conditions.join(" AND "))
+    }
+}
 
-    let resolved_dir = params.direction.unwrap_or(SortDirection::Desc);
+// Bind parameters in the order of build_where_clause placeholders
+fn bind_search_filter<'a>(
+    mut query: BoxedSqlQuery<'a, Pg, SqlQuery>,
+    params: &'a UniteLegaleSearchParams,
+    cursor: Option<&'a UniteLegaleCursor>,
+) -> BoxedSqlQuery<'a, Pg, SqlQuery> {
+    if let Some(ref q) = params.q {
+        query = query.bind::<Text, _>(q);
+    }
 
-    let order_by = match (sort_field, resolved_dir) {
-        (UniteLegaleSortField::Relevance, SortDirection::Asc) => "score ASC",
-        (UniteLegaleSortField::Relevance, SortDirection::Desc) => "score DESC",
-        (UniteLegaleSortField::DateCreation, SortDirection::Asc) => {
-            "u.date_creation ASC NULLS LAST"
-        }
-        (UniteLegaleSortField::DateCreation, SortDirection::Desc) => {
-            "u.date_creation DESC NULLS LAST"
+    if let Some(etat_administratif) = params.etat_administratif {
+        let val = match etat_administratif {
+            common::EtatAdministratif::A => "A",
+            common::EtatAdministratif::F => "F",
+        };
+        query = query.bind::<Text, _>(val);
+    }
+    if let Some(ref activite_principale) = params.activite_principale {
+        query = query.bind::<Text, _>(activite_principale);
+    }
+    if let Some(ref activite_principale_naf25) = params.activite_principale_naf25 {
+        query = query.bind::<Text, _>(activite_principale_naf25);
+    }
+    if let Some(ref categorie_juridique) = params.categorie_juridique {
+        query = query.bind::<Text, _>(categorie_juridique);
+    }
+    if let Some(ref categorie_entreprise) = params.categorie_entreprise {
+        query = query.bind::<Text, _>(categorie_entreprise);
+    }
+    if let Some(date_creation) = params.date_creation {
+        query = query.bind::<Date, _>(date_creation);
+    }
+    if let Some(date_debut) = params.date_debut {
+        query = query.bind::<Date, _>(date_debut);
+    }
+
+    if let Some(cursor) = cursor {
+        if let Some(ref value) = cursor.value {
+            query = query.bind::<Text, _>(value);
         }
-        (UniteLegaleSortField::DateDebut, SortDirection::Asc) => "u.date_debut ASC NULLS LAST",
-        (UniteLegaleSortField::DateDebut, SortDirection::Desc) => "u.date_debut DESC NULLS LAST",
+        query = query.bind::<Text, _>(&cursor.key);
+    }
+
+    query
+}
+
+/// Count the values of each requested facet over the whole filtered set,
+/// ignoring pagination.
+fn search_facets(
+    connection: &mut Connection,
+    params: &UniteLegaleSearchParams,
+    facets: &[UniteLegaleFacet],
+) -> Result<BTreeMap<String, Vec<FacetBucket>>, Error> {
+    let where_clause = build_where_clause(params, None);
+    let text_search = params.q.is_some();
+
+    facets
+        .iter()
+        .map(|facet| {
+            let sql = facet_query(
+                "u",
+                facet.field(),
+                "unite_legale u",
+                &where_clause,
+                text_search,
+            );
+            let query = bind_search_filter(sql_query(&sql).into_boxed(), params, None);
+            let buckets = load_facet(connection, query, text_search)?;
+
+            Ok((facet.field().to_string(), buckets))
+        })
+        .collect()
+}
+
+pub fn search(
+    connection: &mut Connection,
+    params: &UniteLegaleSearchParams,
+) -> Result<UniteLegaleSearchOutput, Error> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+
+    let facets = match params.facets.as_deref() {
+        Some(facets) => Some(parse_facets(facets).map_err(|name| Error::InvalidFacet { name })?),
+        None => None,
     };
 
-    // Assemble query
-    let where_clause = if conditions.is_empty() {
-        String::new()
+    // Resolve sort before building the query, keyset pagination depends on it
+    let (sort_field, resolved_dir) = resolve_sort(params);
+    let sort_key = sort_key(sort_field);
+
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => Some(
+            UniteLegaleCursor::decode(cursor)
+                .filter(|c| c.sort == sort_field && c.direction == resolved_dir)
+                .filter(|_| sort_key.is_some())
+                .ok_or(Error::InvalidCursor)?,
+        ),
+        None => None,
+    };
+
+    let offset = if cursor.is_some() {
+        0
     } else {
-        format!("WHERE {}", conditions.join(" AND "))
+        params.offset.unwrap_or(0).clamp(0, 10_000)
     };
 
+    let where_clause = build_where_clause(
+        params,
+        cursor
+            .as_ref()
+            .zip(sort_key)
+            .map(|(cursor, sort_key)| (cursor, sort_key, resolved_dir)),
+    );
+
+    // Assemble query
     let sql = format!(
         "SELECT {} FROM unite_legale u {} ORDER BY {} LIMIT {} OFFSET {}",
-        select_columns.join(", "),
+        select_columns(params, true, sort_key).join(", "),
         where_clause,
-        order_by,
+        order_by(sort_field, resolved_dir),
         limit,
         offset
     );
 
-    // Bind parameters in order
-    let mut query = sql_query(&sql).into_boxed();
-
-    if let Some(ref q) = params.q {
-        query = query.bind::<Text, _>(q);
-    }
-
-    for (field_name, _) in &field_param_indices {
-        match field_name.as_str() {
-            "etat_administratif" => {
-                let val = match params.etat_administratif.unwrap() {
-                    common::EtatAdministratif::A => "A",
-                    common::EtatAdministratif::F => "F",
-                };
-                query = query.bind::<Text, _>(val);
-            }
-            "activite_principale" => {
-                query = query.bind::<Text, _>(params.activite_principale.as_ref().unwrap());
-            }
-            "categorie_juridique" => {
-                query = query.bind::<Text, _>(params.categorie_juridique.as_ref().unwrap());
-            }
-            "categorie_entreprise" => {
-                query = query.bind::<Text, _>(params.categorie_entreprise.as_ref().unwrap());
-            }
-            "date_creation" => {
-                query = query.bind::<Date, _>(params.date_creation.unwrap());
-            }
-            "date_debut" => {
-                query = query.bind::<Date, _>(params.date_debut.unwrap());
-            }
-            _ => {}
-        }
-    }
-
-    let results = query
+    let results = bind_search_filter(sql_query(&sql).into_boxed(), params, cursor.as_ref())
         .load::<UniteLegaleSearchResult>(connection)
         .map_err(|e| -> Error { e.into() })?;
 
+    // Cursors only continue pages fetched by cursor or from the start
+    let next_cursor = match (sort_key, results.last()) {
+        (Some(_), Some(last)) if offset == 0 && results.len() as i64 == limit => Some(
+            UniteLegaleCursor {
+                sort: sort_field,
+                direction: resolved_dir,
+                value: last.sort_value.clone(),
+                key: last.siren.clone(),
+            }
+            .encode(),
+        ),
+        _ => None,
+    };
+
     Ok(UniteLegaleSearchOutput {
         results,
         limit,
         offset,
         sort: sort_field,
         direction: resolved_dir,
+        next_cursor,
+        facets: match facets {
+            Some(facets) => Some(search_facets(connection, params, &facets)?),
+            None => None,
+        },
     })
 }
 
+/// Stream every result matching the search filters through a server-side
+/// cursor, `on_batch` returns false to stop the export early.
+pub fn export(
+    connection: &mut Connection,
+    params: &UniteLegaleSearchParams,
+    mut on_batch: impl FnMut(Vec<UniteLegaleSearchResult>) -> bool,
+) -> Result<(), Error> {
+    // Sorting the whole set is only done when explicitly asked
+    let order_clause = match params.sort {
+        Some(_) => {
+            let (sort_field, resolved_dir) = resolve_sort(params);
+            format!("ORDER BY {}", order_by(sort_field, resolved_dir))
+        }
+        None => String::new(),
+    };
+
+    let sql = format!(
+        "DECLARE {} NO SCROLL CURSOR FOR SELECT {} FROM unite_legale u {} {}",
+        EXPORT_CURSOR,
+        select_columns(params, false, None).join(", "),
+        build_where_clause(params, None),
+        order_clause
+    );
+
+    connection
+        .build_transaction()
+        .read_only()
+        .run(|conn| {
+            bind_search_filter(sql_query(&sql).into_boxed(), params, None).execute(conn)?;
+
+            loop {
+                let results = sql_query(format!("FETCH {EXPORT_BATCH_SIZE} FROM {EXPORT_CURSOR}"))
+                    .load::<UniteLegaleSearchResult>(conn)?;
+
+                let done = results.len() < EXPORT_BATCH_SIZE;
+
+                if results.is_empty() || !on_batch(results) || done {
+                    break;
+                }
+            }
+
+            Ok(())
+        })
+        .map_err(|e: diesel::result::Error| -> Error { e.into() })
+}
+
+/// Prefix search on names. Candidates are ranked and limited by the bm25
+/// index first so that the siege commune is only joined for returned rows.
+pub fn suggest(
+    connection: &mut Connection,
+    q: &str,
+    limit: i64,
+) -> Result<Vec<UniteLegaleSuggestion>, Error> {
+    sql_query(
+        r#"
+        SELECT
+            u.siren,
+            e.siret::text AS siret_siege,
+            coalesce(
+                u.denomination,
+                u.denomination_usuelle_1,
+                CASE WHEN u.statut_diffusion = 'O'
+                    THEN nullif(trim(coalesce(u.prenom_usuel, '') || ' ' || coalesce(u.nom_usage, u.nom, '')), '')
+                END
+            ) AS nom,
+            e.code_postal,
+            e.libelle_commune,
+            u.etat_administratif
+        FROM (
+            SELECT *, pdb.score(siren) AS score
+            FROM unite_legale
+            WHERE suggest_denomination &&& $1
+            ORDER BY score DESC
+            LIMIT $2
+        ) u
+        LEFT JOIN etablissement e ON e.siret = u.siren || u.nic_siege
+        ORDER BY u.score DESC, u.siren ASC
+        "#,
+    )
+    .bind::<Text, _>(q)
+    .bind::<BigInt, _>(limit)
+    .load::<UniteLegaleSuggestion>(connection)
+    .map_err(|error| error.into())
+}
+
 pub struct UniteLegaleModel {}
 
 #[async_trait]
@@ -343,53 +593,60 @@ impl UpdatableModel for UniteLegaleModel {
             .as_mut()
             .ok_or(UpdatableError::MissingInseeConnector)?;
 
-        let (next_cursor, unites_legales) = insee
+        let (next_cursor, unites_legales, periodes) = insee
             .get_daily_unites_legales(start_timestamp, cursor)
             .await?;
 
         let mut connection = connectors.local.pool.get()?;
 
-        let updated_count = diesel::insert_into(dsl::unite_legale)
-            .values(&unites_legales)
-            .on_conflict(dsl::siren)
-            .do_update()
-            .set((
-                dsl::statut_diffusion.eq(excluded(dsl::statut_diffusion)),
-                dsl::unite_purgee.eq(excluded(dsl::unite_purgee)),
-                dsl::date_creation.eq(excluded(dsl::date_creation)),
-                dsl::sigle.eq(excluded(dsl::sigle)),
-                dsl::sexe.eq(excluded(dsl::sexe)),
-                dsl::prenom_1.eq(excluded(dsl::prenom_1)),
-                dsl::prenom_2.eq(excluded(dsl::prenom_2)),
-                dsl::prenom_3.eq(excluded(dsl::prenom_3)),
-                dsl::prenom_4.eq(excluded(dsl::prenom_4)),
-                dsl::prenom_usuel.eq(excluded(dsl::prenom_usuel)),
-                dsl::pseudonyme.eq(excluded(dsl::pseudonyme)),
-                dsl::identifiant_association.eq(excluded(dsl::identifiant_association)),
-                dsl::tranche_effectifs.eq(excluded(dsl::tranche_effectifs)),
-                dsl::annee_effectifs.eq(excluded(dsl::annee_effectifs)),
-                dsl::date_dernier_traitement.eq(excluded(dsl::date_dernier_traitement)),
-                dsl::nombre_periodes.eq(excluded(dsl::nombre_periodes)),
-                dsl::categorie_entreprise.eq(excluded(dsl::categorie_entreprise)),
-                dsl::annee_categorie_entreprise.eq(excluded(dsl::annee_categorie_entreprise)),
-                dsl::date_debut.eq(excluded(dsl::date_debut)),
-                dsl::etat_administratif.eq(excluded(dsl::etat_administratif)),
-                dsl::nom.eq(excluded(dsl::nom)),
-                dsl::nom_usage.eq(excluded(dsl::nom_usage)),
-                dsl::denomination.eq(excluded(dsl::denomination)),
-                dsl::denomination_usuelle_1.eq(excluded(dsl::denomination_usuelle_1)),
-                dsl::denomination_usuelle_2.eq(excluded(dsl::denomination_usuelle_2)),
-                dsl::denomination_usuelle_3.eq(excluded(dsl::denomination_usuelle_3)),
-                dsl::categorie_juridique.eq(excluded(dsl::categorie_juridique)),
-                dsl::activite_principale.eq(excluded(dsl::activite_principale)),
-                dsl::nomenclature_activite_principale
-                    .eq(excluded(dsl::nomenclature_activite_principale)),
-                dsl::nic_siege.eq(excluded(dsl::nic_siege)),
-                dsl::economie_sociale_solidaire.eq(excluded(dsl::economie_sociale_solidaire)),
-                dsl::societe_mission.eq(excluded(dsl::societe_mission)),
-                dsl::caractere_employeur.eq(excluded(dsl::caractere_employeur)),
-            ))
-            .execute(&mut connection)?;
+        let updated_count = connection.build_transaction().read_write().run(|conn| {
+            let updated_count = diesel::insert_into(dsl::unite_legale)
+                .values(&unites_legales)
+                .on_conflict(dsl::siren)
+                .do_update()
+                .set((
+                    dsl::statut_diffusion.eq(excluded(dsl::statut_diffusion)),
+                    dsl::unite_purgee.eq(excluded(dsl::unite_purgee)),
+                    dsl::date_creation.eq(excluded(dsl::date_creation)),
+                    dsl::sigle.eq(excluded(dsl::sigle)),
+                    dsl::sexe.eq(excluded(dsl::sexe)),
+                    dsl::prenom_1.eq(excluded(dsl::prenom_1)),
+                    dsl::prenom_2.eq(excluded(dsl::prenom_2)),
+                    dsl::prenom_3.eq(excluded(dsl::prenom_3)),
+                    dsl::prenom_4.eq(excluded(dsl::prenom_4)),
+                    dsl::prenom_usuel.eq(excluded(dsl::prenom_usuel)),
+                    dsl::pseudonyme.eq(excluded(dsl::pseudonyme)),
+                    dsl::identifiant_association.eq(excluded(dsl::identifiant_association)),
+                    dsl::tranche_effectifs.eq(excluded(dsl::tranche_effectifs)),
+                    dsl::annee_effectifs.eq(excluded(dsl::annee_effectifs)),
+                    dsl::date_dernier_traitement.eq(excluded(dsl::date_dernier_traitement)),
+                    dsl::nombre_periodes.eq(excluded(dsl::nombre_periodes)),
+                    dsl::categorie_entreprise.eq(excluded(dsl::categorie_entreprise)),
+                    dsl::annee_categorie_entreprise.eq(excluded(dsl::annee_categorie_entreprise)),
+                    dsl::date_debut.eq(excluded(dsl::date_debut)),
+                    dsl::etat_administratif.eq(excluded(dsl::etat_administratif)),
+                    dsl::nom.eq(excluded(dsl::nom)),
+                    dsl::nom_usage.eq(excluded(dsl::nom_usage)),
+                    dsl::denomination.eq(excluded(dsl::denomination)),
+                    dsl::denomination_usuelle_1.eq(excluded(dsl::denomination_usuelle_1)),
+                    dsl::denomination_usuelle_2.eq(excluded(dsl::denomination_usuelle_2)),
+                    dsl::denomination_usuelle_3.eq(excluded(dsl::denomination_usuelle_3)),
+                    dsl::categorie_juridique.eq(excluded(dsl::categorie_juridique)),
+                    dsl::activite_principale.eq(excluded(dsl::activite_principale)),
+                    dsl::nomenclature_activite_principale
+                        .eq(excluded(dsl::nomenclature_activite_principale)),
+                    dsl::nic_siege.eq(excluded(dsl::nic_siege)),
+                    dsl::economie_sociale_solidaire.eq(excluded(dsl::economie_sociale_solidaire)),
+                    dsl::societe_mission.eq(excluded(dsl::societe_mission)),
+                    dsl::caractere_employeur.eq(excluded(dsl::caractere_employeur)),
+                    dsl::activite_principale_naf25.eq(excluded(dsl::activite_principale_naf25)),
+                ))
+                .execute(conn)?;
+
+            super::unite_legale_periode::replace(conn, &periodes)?;
+
+            diesel::QueryResult::Ok(updated_count)
+        })?;
 
         Ok((next_cursor, updated_count))
     }
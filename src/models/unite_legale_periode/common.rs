@@ -0,0 +1,67 @@
+use super::super::schema::unite_legale_periode;
+use super::super::unite_legale::common::UniteLegale;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// State of a legal unit between two changes, the `changement_*` flags
+/// telling which values changed at `date_debut`
+#[derive(Insertable, Queryable, Selectable, ToSchema, Serialize, Clone, Debug)]
+#[diesel(table_name = unite_legale_periode)]
+pub struct UniteLegalePeriode {
+    pub siren: String,
+    pub date_fin: Option<NaiveDate>,
+    pub date_debut: Option<NaiveDate>,
+    pub etat_administratif: String,
+    pub changement_etat_administratif: bool,
+    pub nom: Option<String>,
+    pub changement_nom: bool,
+    pub nom_usage: Option<String>,
+    pub changement_nom_usage: bool,
+    pub denomination: Option<String>,
+    pub changement_denomination: bool,
+    pub denomination_usuelle_1: Option<String>,
+    pub denomination_usuelle_2: Option<String>,
+    pub denomination_usuelle_3: Option<String>,
+    pub changement_denomination_usuelle: bool,
+    pub categorie_juridique: Option<String>,
+    pub changement_categorie_juridique: bool,
+    pub activite_principale: Option<String>,
+    pub nomenclature_activite_principale: Option<String>,
+    pub changement_activite_principale: bool,
+    pub nic_siege: Option<String>,
+    pub changement_nic_siege: bool,
+    pub economie_sociale_solidaire: Option<String>,
+    pub changement_economie_sociale_solidaire: bool,
+    pub societe_mission: Option<String>,
+    pub changement_societe_mission: bool,
+    pub caractere_employeur: Option<String>,
+    pub changement_caractere_employeur: bool,
+}
+
+impl UniteLegalePeriode {
+    /// Replace the values of `unite_legale` varying with periods
+    pub fn apply_to(self, unite_legale: &mut UniteLegale) {
+        // NAF 2025 codes are only known for the current period
+        if self.date_fin.is_some() {
+            unite_legale.activite_principale_naf25 = None;
+        }
+
+        unite_legale.date_debut = self.date_debut;
+        unite_legale.etat_administratif = self.etat_administratif;
+        unite_legale.nom = self.nom;
+        unite_legale.nom_usage = self.nom_usage;
+        unite_legale.denomination = self.denomination;
+        unite_legale.denomination_usuelle_1 = self.denomination_usuelle_1;
+        unite_legale.denomination_usuelle_2 = self.denomination_usuelle_2;
+        unite_legale.denomination_usuelle_3 = self.denomination_usuelle_3;
+        unite_legale.categorie_juridique = self.categorie_juridique;
+        unite_legale.activite_principale = self.activite_principale;
+        unite_legale.nomenclature_activite_principale = self.nomenclature_activite_principale;
+        unite_legale.nic_siege = self.nic_siege;
+        unite_legale.economie_sociale_solidaire = self.economie_sociale_solidaire;
+        unite_legale.societe_mission = self.societe_mission;
+        unite_legale.caractere_employeur = self.caractere_employeur;
+    }
+}
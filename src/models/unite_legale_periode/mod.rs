@@ -0,0 +1,213 @@
+pub mod common;
+pub mod error;
+
+use super::common::{Error as UpdatableError, UpdatableModel, copy_remote_zipped_csv};
+use super::schema::unite_legale_periode::dsl;
+use super::unite_legale::common::UniteLegale;
+use crate::connectors::{Connectors, local::Connection};
+use crate::update::utils::remote_file::RemoteFile;
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveDateTime};
+use common::UniteLegalePeriode;
+use diesel::pg::{CopyFormat, CopyHeader, PgConnection};
+use diesel::prelude::*;
+use diesel::sql_query;
+use error::Error;
+
+// Keeps the bound parameters of an insert below the PostgreSQL limit
+const INSERT_CHUNK_SIZE: usize = 2000;
+
+/// Periods of a legal unit, the most recent first
+pub fn get(connection: &mut Connection, siren: &str) -> Result<Vec<UniteLegalePeriode>, Error> {
+    dsl::unite_legale_periode
+        .select(UniteLegalePeriode::as_select())
+        .filter(dsl::siren.eq(siren))
+        .order(dsl::date_debut.desc().nulls_last())
+        .load::<UniteLegalePeriode>(connection)
+        .map_err(|error| error.into())
+}
+
+/// Rebuild the legal unit as it was on `date`, from the period valid then
+pub fn as_of(
+    connection: &mut Connection,
+    mut unite_legale: UniteLegale,
+    date: NaiveDate,
+) -> Result<UniteLegale, Error> {
+    if unite_legale
+        .date_creation
+        .is_some_and(|date_creation| date_creation > date)
+    {
+        return Err(Error::NotExisting { date });
+    }
+
+    let periode = dsl::unite_legale_periode
+        .select(UniteLegalePeriode::as_select())
+        .filter(dsl::siren.eq(&unite_legale.siren))
+        .filter(dsl::date_debut.is_null().or(dsl::date_debut.le(date)))
+        .filter(dsl::date_fin.is_null().or(dsl::date_fin.ge(date)))
+        .order(dsl::date_debut.desc().nulls_last())
+        .first::<UniteLegalePeriode>(connection)
+        .optional()?
+        .ok_or(Error::NotExisting { date })?;
+
+    periode.apply_to(&mut unite_legale);
+
+    Ok(unite_legale)
+}
+
+/// Replace the whole history of the legal units the periods belong to, as the
+/// INSEE API always returns every period of a legal unit
+pub fn replace(
+    connection: &mut PgConnection,
+    periodes: &[UniteLegalePeriode],
+) -> QueryResult<usize> {
+    let mut sirens: Vec<&String> = periodes.iter().map(|p| &p.siren).collect();
+    sirens.sort_unstable();
+    sirens.dedup();
+
+    diesel::delete(dsl::unite_legale_periode.filter(dsl::siren.eq_any(sirens)))
+        .execute(connection)?;
+
+    let mut inserted_count = 0;
+    for chunk in periodes.chunks(INSERT_CHUNK_SIZE) {
+        inserted_count += diesel::insert_into(dsl::unite_legale_periode)
+            .values(chunk)
+            .execute(connection)?;
+    }
+
+    Ok(inserted_count)
+}
+
+pub struct UniteLegalePeriodeModel {}
+
+#[async_trait]
+impl UpdatableModel for UniteLegalePeriodeModel {
+    fn count(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        dsl::unite_legale_periode
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn count_staging(&self, connectors: &Connectors) -> Result<i64, UpdatableError> {
+        use super::schema::unite_legale_periode_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+        dsl::unite_legale_periode_staging
+            .select(diesel::dsl::count(dsl::id))
+            .first::<i64>(&mut connection)
+            .map_err(|error| error.into())
+    }
+
+    fn insert_remote_file_in_staging(
+        &self,
+        connectors: &Connectors,
+        remote_file: RemoteFile,
+    ) -> Result<bool, UpdatableError> {
+        use super::schema::unite_legale_periode_staging::dsl;
+
+        let mut connection = connectors.local.pool.get()?;
+
+        sql_query("TRUNCATE unite_legale_periode_staging").execute(&mut connection)?;
+
+        diesel::copy_from(dsl::unite_legale_periode_staging)
+            .from_raw_data(
+                (
+                    dsl::siren,
+                    dsl::date_fin,
+                    dsl::date_debut,
+                    dsl::etat_administratif,
+                    dsl::changement_etat_administratif,
+                    dsl::nom,
+                    dsl::changement_nom,
+                    dsl::nom_usage,
+                    dsl::changement_nom_usage,
+                    dsl::denomination,
+                    dsl::changement_denomination,
+                    dsl::denomination_usuelle_1,
+                    dsl::denomination_usuelle_2,
+                    dsl::denomination_usuelle_3,
+                    dsl::changement_denomination_usuelle,
+                    dsl::categorie_juridique,
+                    dsl::changement_categorie_juridique,
+                    dsl::activite_principale,
+                    dsl::nomenclature_activite_principale,
+                    dsl::changement_activite_principale,
+                    dsl::nic_siege,
+                    dsl::changement_nic_siege,
+                    dsl::economie_sociale_solidaire,
+                    dsl::changement_economie_sociale_solidaire,
+                    dsl::societe_mission,
+                    dsl::changement_societe_mission,
+                    dsl::caractere_employeur,
+                    dsl::changement_caractere_employeur,
+                ),
+                |write| copy_remote_zipped_csv(remote_file.to_reader(), write),
+            )
+            .with_delimiter(',')
+            .with_format(CopyFormat::Csv)
+            .with_header(CopyHeader::Set(true))
+            .execute(&mut connection)
+            .map(|count| count > 0)
+            .map_err(|error| error.into())
+    }
+
+    fn swap(&self, connectors: &Connectors) -> Result<(), UpdatableError> {
+        let mut connection = connectors.local.pool.get()?;
+        connection.build_transaction().read_write().run(|conn| {
+            sql_query("ALTER TABLE unite_legale_periode RENAME TO unite_legale_periode_temp")
+                .execute(conn)?;
+            sql_query("ALTER TABLE unite_legale_periode_staging RENAME TO unite_legale_periode")
+                .execute(conn)?;
+            sql_query(
+                "ALTER TABLE unite_legale_periode_temp RENAME TO unite_legale_periode_staging",
+            )
+            .execute(conn)?;
+            sql_query("TRUNCATE unite_legale_periode_staging").execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET last_imported_timestamp = staging_imported_timestamp
+                WHERE group_type = 'unites_legales_periodes'
+                "#,
+            )
+            .execute(conn)?;
+            sql_query(
+                r#"
+                UPDATE group_metadata
+                SET staging_imported_timestamp = NULL
+                WHERE group_type = 'unites_legales_periodes'
+                "#,
+            )
+            .execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    // Periods are synced along with their legal unit, see `replace`
+    async fn get_total_count(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+    ) -> Result<u32, UpdatableError> {
+        Ok(0)
+    }
+
+    fn get_last_insee_synced_timestamp(
+        &self,
+        _connectors: &Connectors,
+    ) -> Result<Option<NaiveDateTime>, UpdatableError> {
+        Ok(None)
+    }
+
+    async fn update_daily_data(
+        &self,
+        _connectors: &mut Connectors,
+        _start_timestamp: NaiveDateTime,
+        _cursor: String,
+    ) -> Result<(Option<String>, usize), UpdatableError> {
+        Ok((None, 0))
+    }
+}
@@ -58,6 +58,11 @@ pub enum SyntheticGroupType {
     UnitesLegales,
     Etablissements,
     LiensSuccession,
+    UnitesLegalesPeriodes,
+    EtablissementsPeriodes,
+    NafCorrespondances,
+    Nomenclatures,
+    Cog,
     All,
 }
 
@@ -127,10 +132,18 @@ impl From<SyntheticGroupType> for Vec<GroupType> {
             SyntheticGroupType::UnitesLegales => vec![GroupType::UnitesLegales],
             SyntheticGroupType::Etablissements => vec![GroupType::Etablissements],
             SyntheticGroupType::LiensSuccession => vec![GroupType::LiensSuccession],
+            SyntheticGroupType::UnitesLegalesPeriodes => vec![GroupType::UnitesLegalesPeriodes],
+            SyntheticGroupType::EtablissementsPeriodes => vec![GroupType::EtablissementsPeriodes],
+            SyntheticGroupType::NafCorrespondances => vec![GroupType::NafCorrespondances],
+            SyntheticGroupType::Nomenclatures => vec![GroupType::Nomenclatures],
+            SyntheticGroupType::Cog => vec![GroupType::Cog],
             SyntheticGroupType::All => vec![
                 GroupType::UnitesLegales,
                 GroupType::Etablissements,
                 GroupType::LiensSuccession,
+                GroupType::UnitesLegalesPeriodes,
+                GroupType::EtablissementsPeriodes,
+                GroupType::Cog,
             ],
         }
     }
@@ -143,6 +156,15 @@ impl ToSql<Text, Pg> for SyntheticGroupType {
             SyntheticGroupType::UnitesLegales => out.write_all(b"unites_legales")?,
             SyntheticGroupType::Etablissements => out.write_all(b"etablissements")?,
             SyntheticGroupType::LiensSuccession => out.write_all(b"liens_succession")?,
+            SyntheticGroupType::UnitesLegalesPeriodes => {
+                out.write_all(b"unites_legales_periodes")?
+            }
+            SyntheticGroupType::EtablissementsPeriodes => {
+                out.write_all(b"etablissements_periodes")?
+            }
+            SyntheticGroupType::NafCorrespondances => out.write_all(b"naf_correspondances")?,
+            SyntheticGroupType::Nomenclatures => out.write_all(b"nomenclatures")?,
+            SyntheticGroupType::Cog => out.write_all(b"cog")?,
             SyntheticGroupType::All => out.write_all(b"all")?,
         }
         Ok(IsNull::No)
@@ -155,6 +177,11 @@ impl FromSql<Text, Pg> for SyntheticGroupType {
             b"unites_legales" => Ok(SyntheticGroupType::UnitesLegales),
             b"etablissements" => Ok(SyntheticGroupType::Etablissements),
             b"liens_succession" => Ok(SyntheticGroupType::LiensSuccession),
+            b"unites_legales_periodes" => Ok(SyntheticGroupType::UnitesLegalesPeriodes),
+            b"etablissements_periodes" => Ok(SyntheticGroupType::EtablissementsPeriodes),
+            b"naf_correspondances" => Ok(SyntheticGroupType::NafCorrespondances),
+            b"nomenclatures" => Ok(SyntheticGroupType::Nomenclatures),
+            b"cog" => Ok(SyntheticGroupType::Cog),
             b"all" => Ok(SyntheticGroupType::All),
             _ => Err("Unrecognized enum variant".into()),
         }
@@ -167,6 +194,11 @@ impl std::fmt::Display for SyntheticGroupType {
             SyntheticGroupType::UnitesLegales => write!(f, "unités légales"),
             SyntheticGroupType::Etablissements => write!(f, "établissements"),
             SyntheticGroupType::LiensSuccession => write!(f, "liens de succession"),
+            SyntheticGroupType::UnitesLegalesPeriodes => write!(f, "périodes des unités légales"),
+            SyntheticGroupType::EtablissementsPeriodes => write!(f, "périodes des établissements"),
+            SyntheticGroupType::NafCorrespondances => write!(f, "correspondances NAF"),
+            SyntheticGroupType::Nomenclatures => write!(f, "nomenclatures"),
+            SyntheticGroupType::Cog => write!(f, "code officiel géographique"),
             SyntheticGroupType::All => write!(f, "all"),
         }
     }
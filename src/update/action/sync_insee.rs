@@ -20,8 +20,18 @@ impl Action for SyncInseeAction {
     ) -> Result<(), Error> {
         debug!("Syncing {:#?}", group_type);
 
-        // Use Insee connector only if present
-        if connectors.insee.is_some() {
+        // Some groups are filled by the sync of another one
+        if let Some(parent_group_type) = group_type.synced_with() {
+            summary_delegate.finish(
+                connectors,
+                format!("synced with {parent_group_type}"),
+                0,
+                false,
+            )?;
+        } else if !group_type.insee_synced() {
+            summary_delegate.finish(connectors, String::from("not synced with insee"), 0, false)?;
+        } else if connectors.insee.is_some() {
+            // Use Insee connector only if present
             let model = group_type.get_updatable_model();
 
             if let Some(last_timestamp) = model.get_last_insee_synced_timestamp(connectors)? {
@@ -24,6 +24,10 @@ impl Action for UpdateAction {
 
         let metadata = group_metadata::get(connectors, group_type)?;
 
+        if metadata.url.is_empty() {
+            return Err(Error::MissingUrl { group_type });
+        }
+
         let remote_file = RemoteFile::new(metadata.url.clone()).await?;
         let last_modified = remote_file.last_modified;
 
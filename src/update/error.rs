@@ -17,6 +17,7 @@ custom_error! { pub Error
     FileCSVCreation {io_error: std::io::Error} = "Unable to create CSV file for unzip ({io_error})",
     FileCSVCopy {io_error: std::io::Error} = "Unable to copy CSV file from archive ({io_error})",
     FileCSVPermission {io_error: std::io::Error} = "Unable to set permission for CSV file ({io_error})",
+    MissingUrl {group_type: GroupType} = "No file to download for {group_type}, set the url of its group_metadata row",
     Download {req_error: reqwest::Error} = "Unable to download data from remote server ({req_error})",
     ZipOpen {io_error: std::io::Error} = "Unable to open data zip file ({io_error})",
     ZipDecode {zip_error: zip::result::ZipError} = "Unable to decode zip file ({zip_error})",